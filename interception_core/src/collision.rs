@@ -0,0 +1,591 @@
+use pyo3::prelude::*;
+use crate::ops::{cos, sin, sqrt};
+use crate::structs::{Annulus, Circle, Point, AABB};
+
+/// A convex polygon given as `vertices` relative to `position`, so the shape can be moved by
+/// updating one field instead of rewriting every vertex. Vertices may be wound in either
+/// order; [`separation`] only depends on the edge directions, not their orientation.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Polygon {
+    #[pyo3(get, set)]
+    pub position: Point,
+    #[pyo3(get, set)]
+    pub vertices: Vec<Point>,
+}
+
+#[pymethods]
+impl Polygon {
+    #[new]
+    pub fn new(position: Point, vertices: Vec<Point>) -> Self {
+        Polygon { position, vertices }
+    }
+
+    /// Vertices translated into world space.
+    pub fn world_vertices(&self) -> Vec<Point> {
+        self.vertices
+            .iter()
+            .map(|v| Point::new(self.position.x + v.x, self.position.y + v.y))
+            .collect()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("Polygon(position={}, vertices={})", self.position.__repr__(), self.vertices.len())
+    }
+}
+
+/// A line segment `a`-`b` thickened by `radius` - a stadium shape for non-circular agent
+/// bodies (e.g. an elongated vehicle) that still collide cheaply.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct Capsule {
+    #[pyo3(get, set)]
+    pub a: Point,
+    #[pyo3(get, set)]
+    pub b: Point,
+    #[pyo3(get, set)]
+    pub radius: f64,
+}
+
+#[pymethods]
+impl Capsule {
+    #[new]
+    pub fn new(a: Point, b: Point, radius: f64) -> Self {
+        Capsule { a, b, radius }
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("Capsule(a={}, b={}, radius={:.3})", self.a.__repr__(), self.b.__repr__(), self.radius)
+    }
+}
+
+/// Any of the convex shapes the collision subsystem understands, so [`separation`] and
+/// [`overlaps`] can dispatch on a single type instead of needing one overload per shape pair.
+#[derive(Debug, Clone)]
+pub enum Shape {
+    Circle(Circle),
+    Polygon(Polygon),
+    Capsule(Capsule),
+    Aabb(AABB),
+}
+
+/// Whether `a` and `b` overlap at all - `separation(a, b).is_some()`, for callers that only
+/// need the boolean.
+pub fn overlaps(a: &Shape, b: &Shape) -> bool {
+    separation(a, b).is_some()
+}
+
+/// Minimum translation vector to push `a` out of `b`, plus the penetration depth, via the
+/// Separating Axis Theorem - a generalization of [`Circle::separation`] to polygons and
+/// capsules.
+///
+/// Tests each candidate axis in turn: every polygon/AABB edge normal from either shape, each
+/// capsule's own side normal, and - for circles and capsules, which have no edges of their
+/// own - the axis from their nearest point to the nearest point of the other shape (for a
+/// circle this nearest point is always its center, so that axis is exactly "circle center to
+/// nearest vertex of the other shape"). Both shapes are projected onto each axis; if any axis
+/// shows a gap between the projections, the shapes don't overlap and this returns `None`.
+/// Otherwise the axis with the *smallest* overlap gives the MTV, oriented using the shapes'
+/// representative points so it points from `b` toward `a`.
+pub fn separation(a: &Shape, b: &Shape) -> Option<(Point, f64)> {
+    let axes = candidate_axes(a, b);
+
+    let mut best_overlap = f64::INFINITY;
+    let mut best_axis = Point::new(1.0, 0.0);
+
+    for axis in &axes {
+        let (min_a, max_a) = project(a, axis);
+        let (min_b, max_b) = project(b, axis);
+        let overlap = max_a.min(max_b) - min_a.max(min_b);
+
+        if overlap <= 0.0 {
+            return None;
+        }
+        if overlap < best_overlap {
+            best_overlap = overlap;
+            best_axis = axis.clone();
+        }
+    }
+
+    if !best_overlap.is_finite() {
+        return None;
+    }
+
+    let from_b_to_a = Point::new(
+        representative_point(a).x - representative_point(b).x,
+        representative_point(a).y - representative_point(b).y,
+    );
+    let sign = if dot(&from_b_to_a, &best_axis) < 0.0 { -1.0 } else { 1.0 };
+
+    Some((
+        Point::new(best_axis.x * best_overlap * sign, best_axis.y * best_overlap * sign),
+        best_overlap,
+    ))
+}
+
+fn candidate_axes(a: &Shape, b: &Shape) -> Vec<Point> {
+    let mut axes = Vec::new();
+
+    if let Some(vertices) = polygon_vertices(a) {
+        axes.extend(edge_normals(&vertices));
+    }
+    if let Some(vertices) = polygon_vertices(b) {
+        axes.extend(edge_normals(&vertices));
+    }
+    if let Shape::Capsule(capsule) = a {
+        axes.extend(capsule_side_axis(capsule));
+    }
+    if let Shape::Capsule(capsule) = b {
+        axes.extend(capsule_side_axis(capsule));
+    }
+
+    axes.extend(rounded_axis(a, b));
+    axes.extend(rounded_axis(b, a));
+
+    axes
+}
+
+/// For a circle or capsule `round`, the axis from its nearest point to `other`'s nearest
+/// point to that point - the standard "closest vertex" SAT axis needed when a shape has no
+/// edges of its own to contribute. `None` for polygons/AABBs, which get their axes from
+/// [`edge_normals`] instead.
+fn rounded_axis(round: &Shape, other: &Shape) -> Option<Point> {
+    match round {
+        Shape::Circle(_) | Shape::Capsule(_) => {
+            let anchor = nearest_point_on(round, &representative_point(other));
+            let target = nearest_point_on(other, &anchor);
+            normalize(Point::new(target.x - anchor.x, target.y - anchor.y))
+        }
+        _ => None,
+    }
+}
+
+fn polygon_vertices(shape: &Shape) -> Option<Vec<Point>> {
+    match shape {
+        Shape::Polygon(polygon) => Some(polygon.world_vertices()),
+        Shape::Aabb(aabb) => Some(vec![
+            Point::new(aabb.min.x, aabb.min.y),
+            Point::new(aabb.max.x, aabb.min.y),
+            Point::new(aabb.max.x, aabb.max.y),
+            Point::new(aabb.min.x, aabb.max.y),
+        ]),
+        Shape::Circle(_) | Shape::Capsule(_) => None,
+    }
+}
+
+fn edge_normals(vertices: &[Point]) -> Vec<Point> {
+    let n = vertices.len();
+    (0..n)
+        .filter_map(|i| {
+            let a = &vertices[i];
+            let b = &vertices[(i + 1) % n];
+            normalize(Point::new(-(b.y - a.y), b.x - a.x))
+        })
+        .collect()
+}
+
+fn capsule_side_axis(capsule: &Capsule) -> Option<Point> {
+    normalize(Point::new(-(capsule.b.y - capsule.a.y), capsule.b.x - capsule.a.x))
+}
+
+fn nearest_point_on(shape: &Shape, from: &Point) -> Point {
+    match shape {
+        Shape::Circle(circle) => circle.center.clone(),
+        Shape::Capsule(capsule) => closest_point_on_segment(from, &capsule.a, &capsule.b),
+        Shape::Polygon(polygon) => nearest_vertex(&polygon.world_vertices(), from)
+            .unwrap_or_else(|| polygon.position.clone()),
+        Shape::Aabb(_) => nearest_vertex(&polygon_vertices(shape).unwrap(), from)
+            .unwrap_or_else(|| representative_point(shape)),
+    }
+}
+
+/// Closest of `vertices` to `from`, or `None` for a vertex-less (degenerate) shape - the
+/// caller falls back to that shape's anchor point instead of treating this as an error.
+fn nearest_vertex(vertices: &[Point], from: &Point) -> Option<Point> {
+    vertices
+        .iter()
+        .min_by(|p, q| from.distance_to(p).partial_cmp(&from.distance_to(q)).unwrap_or(std::cmp::Ordering::Equal))
+        .cloned()
+}
+
+fn closest_point_on_segment(point: &Point, a: &Point, b: &Point) -> Point {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let len_sq = dx * dx + dy * dy;
+    if len_sq < 1e-12 {
+        return a.clone();
+    }
+    let t = (((point.x - a.x) * dx + (point.y - a.y) * dy) / len_sq).max(0.0).min(1.0);
+    Point::new(a.x + t * dx, a.y + t * dy)
+}
+
+fn representative_point(shape: &Shape) -> Point {
+    match shape {
+        Shape::Circle(circle) => circle.center.clone(),
+        Shape::Capsule(capsule) => Point::new((capsule.a.x + capsule.b.x) / 2.0, (capsule.a.y + capsule.b.y) / 2.0),
+        Shape::Polygon(polygon) => centroid(&polygon.world_vertices()),
+        Shape::Aabb(aabb) => Point::new((aabb.min.x + aabb.max.x) / 2.0, (aabb.min.y + aabb.max.y) / 2.0),
+    }
+}
+
+fn centroid(points: &[Point]) -> Point {
+    let sum = points.iter().fold(Point::new(0.0, 0.0), |acc, p| Point::new(acc.x + p.x, acc.y + p.y));
+    Point::new(sum.x / points.len() as f64, sum.y / points.len() as f64)
+}
+
+fn project(shape: &Shape, axis: &Point) -> (f64, f64) {
+    match shape {
+        Shape::Circle(circle) => {
+            let center = dot(&circle.center, axis);
+            (center - circle.radius, center + circle.radius)
+        }
+        Shape::Capsule(capsule) => {
+            let pa = dot(&capsule.a, axis);
+            let pb = dot(&capsule.b, axis);
+            let (lo, hi) = if pa <= pb { (pa, pb) } else { (pb, pa) };
+            (lo - capsule.radius, hi + capsule.radius)
+        }
+        Shape::Polygon(polygon) => project_points(&polygon.world_vertices(), axis),
+        Shape::Aabb(_) => project_points(&polygon_vertices(shape).unwrap(), axis),
+    }
+}
+
+fn project_points(points: &[Point], axis: &Point) -> (f64, f64) {
+    let mut min = f64::INFINITY;
+    let mut max = f64::NEG_INFINITY;
+    for point in points {
+        let d = dot(point, axis);
+        min = min.min(d);
+        max = max.max(d);
+    }
+    (min, max)
+}
+
+fn dot(point: &Point, axis: &Point) -> f64 {
+    point.x * axis.x + point.y * axis.y
+}
+
+fn normalize(v: Point) -> Option<Point> {
+    let mag = sqrt(v.x * v.x + v.y * v.y);
+    if mag < 1e-9 {
+        None
+    } else {
+        Some(Point::new(v.x / mag, v.y / mag))
+    }
+}
+
+/// A shape that can report its own bounding volumes, so a broad phase can prune pairs of
+/// non-interacting shapes before a caller runs exact geometry (`separation`,
+/// `calculate_apollonian_circle`/`circle_intersection_points`) on the survivors.
+///
+/// `translation` and `rotation` let a caller ask "what would my bounds be at this pose"
+/// without first materializing a moved/rotated copy of the shape - e.g. to bound a predicted
+/// future position.
+pub trait Bounded2d {
+    /// The tightest axis-aligned box enclosing this shape translated by `translation` and
+    /// rotated by `rotation` radians about the shape's own reference point (its center for
+    /// `Circle`/`Annulus`, `position` for `Polygon`, midpoint for `Capsule`).
+    fn aabb(&self, translation: &Point, rotation: f64) -> AABB;
+
+    /// A circle enclosing this shape, ignoring any translation/rotation, centered on the same
+    /// reference point as `aabb` (so for an off-center `Polygon` not the smallest one) - a
+    /// cheaper (if looser) bound than `aabb` for a first broad-phase pass.
+    fn bounding_circle(&self) -> Circle;
+}
+
+impl Bounded2d for Circle {
+    fn aabb(&self, translation: &Point, _rotation: f64) -> AABB {
+        // A circle is rotation-invariant about its own center, so only translation matters.
+        let center = Point::new(self.center.x + translation.x, self.center.y + translation.y);
+        AABB::new(
+            Point::new(center.x - self.radius, center.y - self.radius),
+            Point::new(center.x + self.radius, center.y + self.radius),
+        )
+    }
+
+    fn bounding_circle(&self) -> Circle {
+        self.clone()
+    }
+}
+
+impl Bounded2d for Annulus {
+    fn aabb(&self, translation: &Point, _rotation: f64) -> AABB {
+        let center = Point::new(self.center.x + translation.x, self.center.y + translation.y);
+        AABB::new(
+            Point::new(center.x - self.outer, center.y - self.outer),
+            Point::new(center.x + self.outer, center.y + self.outer),
+        )
+    }
+
+    fn bounding_circle(&self) -> Circle {
+        Circle::new(self.center.clone(), self.outer)
+    }
+}
+
+impl Bounded2d for Polygon {
+    fn aabb(&self, translation: &Point, rotation: f64) -> AABB {
+        let pivot = &self.position;
+        let points: Vec<Point> = self
+            .world_vertices()
+            .iter()
+            .map(|v| {
+                let rotated = rotate_point(v, pivot, rotation);
+                Point::new(rotated.x + translation.x, rotated.y + translation.y)
+            })
+            .collect();
+        aabb_of_points(&points)
+    }
+
+    fn bounding_circle(&self) -> Circle {
+        let vertices = self.world_vertices();
+        let radius = vertices.iter().map(|v| self.position.distance_to(v)).fold(0.0_f64, f64::max);
+        Circle::new(self.position.clone(), radius)
+    }
+}
+
+impl Bounded2d for Capsule {
+    fn aabb(&self, translation: &Point, rotation: f64) -> AABB {
+        let pivot = representative_point(&Shape::Capsule(self.clone()));
+        let a = rotate_point(&self.a, &pivot, rotation);
+        let b = rotate_point(&self.b, &pivot, rotation);
+        AABB::new(
+            Point::new(a.x.min(b.x) - self.radius + translation.x, a.y.min(b.y) - self.radius + translation.y),
+            Point::new(a.x.max(b.x) + self.radius + translation.x, a.y.max(b.y) + self.radius + translation.y),
+        )
+    }
+
+    fn bounding_circle(&self) -> Circle {
+        let center = representative_point(&Shape::Capsule(self.clone()));
+        Circle::new(center.clone(), center.distance_to(&self.a) + self.radius)
+    }
+}
+
+fn rotate_point(point: &Point, pivot: &Point, rotation: f64) -> Point {
+    if rotation == 0.0 {
+        return point.clone();
+    }
+    let (sin_r, cos_r) = (sin(rotation), cos(rotation));
+    let dx = point.x - pivot.x;
+    let dy = point.y - pivot.y;
+    Point::new(pivot.x + dx * cos_r - dy * sin_r, pivot.y + dx * sin_r + dy * cos_r)
+}
+
+fn aabb_of_points(points: &[Point]) -> AABB {
+    let mut min = Point::new(f64::INFINITY, f64::INFINITY);
+    let mut max = Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for point in points {
+        min.x = min.x.min(point.x);
+        min.y = min.y.min(point.y);
+        max.x = max.x.max(point.x);
+        max.y = max.y.max(point.y);
+    }
+    AABB::new(min, max)
+}
+
+/// Cheap whole-box operations on an axis-aligned bounding volume, kept separate from
+/// `Bounded2d` (which asks a *shape* for its bounds) since these instead combine or adjust
+/// boxes that have already been computed.
+pub trait BoundingVolume: Sized {
+    /// The smallest box enclosing both `self` and `other`.
+    fn merge(&self, other: &Self) -> Self;
+    /// `self` expanded outward by `amount` on every side (a safety margin for a swept query).
+    fn grow(&self, amount: f64) -> Self;
+    /// `self` contracted inward by `amount` on every side; equivalent to `grow(-amount)`.
+    fn shrink(&self, amount: f64) -> Self;
+    /// Whether `self` and `other` overlap - the broad-phase test, far cheaper than any exact
+    /// shape-vs-shape check.
+    fn intersects(&self, other: &Self) -> bool;
+}
+
+impl BoundingVolume for AABB {
+    fn merge(&self, other: &AABB) -> AABB {
+        AABB::new(
+            Point::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            Point::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        )
+    }
+
+    fn grow(&self, amount: f64) -> AABB {
+        AABB::new(
+            Point::new(self.min.x - amount, self.min.y - amount),
+            Point::new(self.max.x + amount, self.max.y + amount),
+        )
+    }
+
+    fn shrink(&self, amount: f64) -> AABB {
+        self.grow(-amount)
+    }
+
+    fn intersects(&self, other: &AABB) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x && self.min.y <= other.max.y && self.max.y >= other.min.y
+    }
+}
+
+/// Candidate pairs among `shapes` whose untranslated, unrotated AABBs overlap - an O(n^2)
+/// broad phase, but over cheap box tests rather than the exact geometry (`separation`,
+/// `calculate_apollonian_circle`/`circle_intersection_points`) a caller would otherwise run on
+/// every pair. Pairs this rejects can never interact; pairs it keeps still need the exact
+/// check to confirm they actually do.
+pub fn broad_phase_pairs<T: Bounded2d>(shapes: &[T]) -> Vec<(usize, usize)> {
+    let origin = Point::new(0.0, 0.0);
+    let boxes: Vec<AABB> = shapes.iter().map(|shape| shape.aabb(&origin, 0.0)).collect();
+
+    let mut pairs = Vec::new();
+    for i in 0..boxes.len() {
+        for j in (i + 1)..boxes.len() {
+            if boxes[i].intersects(&boxes[j]) {
+                pairs.push((i, j));
+            }
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_square(position: Point) -> Polygon {
+        Polygon::new(
+            position,
+            vec![
+                Point::new(-1.0, -1.0),
+                Point::new(1.0, -1.0),
+                Point::new(1.0, 1.0),
+                Point::new(-1.0, 1.0),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_polygon_world_vertices_translates_by_position() {
+        let polygon = unit_square(Point::new(3.0, 4.0));
+        let vertices = polygon.world_vertices();
+
+        assert_eq!(vertices[0].x, 2.0);
+        assert_eq!(vertices[0].y, 3.0);
+        assert_eq!(vertices[2].x, 4.0);
+        assert_eq!(vertices[2].y, 5.0);
+    }
+
+    #[test]
+    fn test_circle_vs_polygon_separation_when_overlapping() {
+        let circle = Shape::Circle(Circle::new(Point::new(0.0, 0.0), 1.0));
+        let polygon = Shape::Polygon(unit_square(Point::new(1.5, 0.0)));
+
+        let (mtv, depth) = separation(&circle, &polygon).expect("circle overlaps the square");
+
+        assert!((depth - 0.5).abs() < 1e-9);
+        assert!(mtv.x < 0.0);
+        assert!(mtv.y.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_circle_vs_polygon_no_separation_when_disjoint() {
+        let circle = Shape::Circle(Circle::new(Point::new(0.0, 0.0), 1.0));
+        let polygon = Shape::Polygon(unit_square(Point::new(5.0, 0.0)));
+
+        assert!(separation(&circle, &polygon).is_none());
+        assert!(!overlaps(&circle, &polygon));
+    }
+
+    #[test]
+    fn test_polygon_vs_polygon_separation_along_shared_x_overlap() {
+        let a = Shape::Polygon(unit_square(Point::new(0.0, 0.0)));
+        let b = Shape::Polygon(unit_square(Point::new(1.5, 0.0)));
+
+        let (mtv, depth) = separation(&a, &b).expect("squares overlap along x");
+
+        assert!((depth - 0.5).abs() < 1e-9);
+        assert!(mtv.x < 0.0);
+        assert!(mtv.y.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_capsule_vs_circle_separation_pushes_apart_along_shared_axis() {
+        let capsule = Shape::Capsule(Capsule::new(Point::new(-2.0, 0.0), Point::new(2.0, 0.0), 1.0));
+        let circle = Shape::Circle(Circle::new(Point::new(0.0, 1.5), 1.0));
+
+        let (mtv, depth) = separation(&capsule, &circle).expect("capsule and circle overlap");
+
+        assert!((depth - 0.5).abs() < 1e-9);
+        assert!(mtv.y < 0.0);
+        assert!(mtv.x.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_capsule_vs_circle_no_separation_when_far_apart() {
+        let capsule = Shape::Capsule(Capsule::new(Point::new(-2.0, 0.0), Point::new(2.0, 0.0), 1.0));
+        let circle = Shape::Circle(Circle::new(Point::new(0.0, 5.0), 1.0));
+
+        assert!(separation(&capsule, &circle).is_none());
+    }
+
+    #[test]
+    fn test_circle_vs_aabb_matches_equivalent_polygon() {
+        let circle = Shape::Circle(Circle::new(Point::new(0.0, 0.0), 1.0));
+        let aabb = Shape::Aabb(AABB::new(Point::new(0.5, -1.0), Point::new(2.5, 1.0)));
+
+        let (mtv, depth) = separation(&circle, &aabb).expect("circle overlaps the box");
+
+        assert!((depth - 0.5).abs() < 1e-9);
+        assert!(mtv.x < 0.0);
+        assert!(mtv.y.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_circle_bounded2d_aabb_and_bounding_circle() {
+        let circle = Circle::new(Point::new(2.0, 3.0), 1.0);
+
+        let aabb = circle.aabb(&Point::new(0.0, 0.0), 0.0);
+        assert_eq!(aabb.min.x, 1.0);
+        assert_eq!(aabb.min.y, 2.0);
+        assert_eq!(aabb.max.x, 3.0);
+        assert_eq!(aabb.max.y, 4.0);
+
+        let bounding = circle.bounding_circle();
+        assert_eq!(bounding.radius, 1.0);
+    }
+
+    #[test]
+    fn test_polygon_and_capsule_bounding_circle_radii() {
+        let polygon = unit_square(Point::new(0.0, 0.0));
+        let bounding = polygon.bounding_circle();
+        assert!((bounding.radius - 2.0_f64.sqrt()).abs() < 1e-9);
+
+        let capsule = Capsule::new(Point::new(-2.0, 0.0), Point::new(2.0, 0.0), 1.0);
+        let bounding = capsule.bounding_circle();
+        assert!((bounding.radius - 3.0).abs() < 1e-9);
+        assert_eq!(bounding.center.x, 0.0);
+        assert_eq!(bounding.center.y, 0.0);
+    }
+
+    #[test]
+    fn test_aabb_merge_grow_shrink_and_intersects() {
+        let a = AABB::new(Point::new(0.0, 0.0), Point::new(1.0, 1.0));
+        let b = AABB::new(Point::new(2.0, 2.0), Point::new(3.0, 3.0));
+
+        let merged = a.merge(&b);
+        assert_eq!(merged.min.x, 0.0);
+        assert_eq!(merged.max.x, 3.0);
+
+        assert!(!a.intersects(&b));
+        let grown = a.grow(1.5);
+        assert!(grown.intersects(&b));
+        assert_eq!(grown.shrink(1.5).min.x, a.min.x);
+    }
+
+    #[test]
+    fn test_broad_phase_pairs_skips_distant_shape() {
+        let circles = vec![
+            Circle::new(Point::new(0.0, 0.0), 0.5),
+            Circle::new(Point::new(0.8, 0.0), 0.5),
+            Circle::new(Point::new(100.0, 100.0), 0.5),
+        ];
+
+        let pairs = broad_phase_pairs(&circles);
+
+        assert!(pairs.contains(&(0, 1)));
+        assert!(!pairs.iter().any(|&(a, b)| a == 2 || b == 2));
+    }
+}
@@ -1,57 +1,141 @@
-use crate::structs::{Point, Circle, AgentState, WorldState, SimConfig, ControlState, Arc};
-use crate::geometry::{calculate_apollonian_circle, calculate_arc_intersection_length, circle_intersection_points, calculate_line_segment_circle_intersection};
+use crate::ops::{cos, sin, sqrt};
+use crate::structs::{Point, Circle, AgentState, WorldState, SimConfig, ControlState, Arc, CollisionMode, DefenderFsmState};
+use crate::geometry::{calculate_apollonian_circle, calculate_arc_intersection_length, circle_intersection_points, calculate_line_segment_circle_intersection, calculate_interception_point, SpatialGrid};
+use crate::collision;
 use std::f64::consts::PI;
 
-/// Determine control state based on intended strategy priority
-/// 1. Intercept if Apollonian circle intersects intruder's path (highest priority)
-/// 2. Engage if Apollonian circle touches goal circle (use loss function)
-/// 3. Travel if Apollonian circle doesn't touch goal (move circle towards goal)
+/// True once the intruder has slipped past the defender relative to the protected zone,
+/// i.e. the defender's Apollonian circle can no longer separate the intruder from the
+/// zone - the defender needs to fall back and re-establish a guarding position.
+fn intruder_behind_defender(defender_pos: &Point, intruder_pos: &Point, protected_center: &Point) -> bool {
+    // Direction the defender is holding its guard line, outward from the zone center.
+    let away_from_zone = Point::new(defender_pos.x - protected_center.x, defender_pos.y - protected_center.y);
+    let to_intruder = Point::new(intruder_pos.x - defender_pos.x, intruder_pos.y - defender_pos.y);
+    // If the intruder lies back toward the zone relative to the defender's guard line,
+    // the defender no longer separates it from the zone.
+    away_from_zone.x * to_intruder.x + away_from_zone.y * to_intruder.y < 0.0
+}
+
+/// Determine control state based on intended strategy priority, with hysteresis:
+/// 1. Retreat if the intruder has slipped past the defender relative to the zone
+/// 2. Intercept if a swept interception of the intruder is feasible (highest-priority pursuit)
+/// 3. Engage if the Apollonian circle touches the protected zone (cooperative defense)
+/// 4. Travel if the Apollonian circle doesn't touch the zone (move circle towards goal)
+///
+/// A transition away from the current state only fires once its triggering condition has
+/// held for `config.dwell_steps` consecutive calls, to avoid chatter near a boundary.
+/// Intercept additionally tolerates `config.intercept_cooldown_steps` of infeasibility
+/// before de-committing, since a single bad reading shouldn't abandon a close pursuit.
 pub fn determine_next_control_state(
-    current_state: &ControlState,
+    fsm_state: &mut DefenderFsmState,
     apollonian_circle: &Circle,
     protected_zone: &Circle,
+    defender_pos: &Point,
     intruder_pos: &Point,
+    intruder_vel: &Point,
     protected_center: &Point,
+    defender_speed: f64,
+    config: &SimConfig,
 ) -> ControlState {
-    // Intercept is terminal - once committed, stay committed
-    if *current_state == ControlState::Intercept {
-        return ControlState::Intercept;
-    }
-    
-    // Priority 1: Check for interception opportunity FIRST (highest priority)
-    if let Some(_) = calculate_line_segment_circle_intersection(
-        intruder_pos,
-        protected_center,
-        apollonian_circle
-    ) {
-        return ControlState::Intercept;
-    }
-    
-    // Priority 2: If Apollonian circle intersects protected zone, use cooperative defense
-    if apollonian_circle.intersects(protected_zone) {
+    let interception_feasible =
+        calculate_interception_point(intruder_pos, intruder_vel, defender_pos, defender_speed).is_some();
+
+    if fsm_state.control_state == ControlState::Intercept {
+        if interception_feasible {
+            fsm_state.intercept_infeasible_steps = 0;
+            return ControlState::Intercept;
+        }
+
+        fsm_state.intercept_infeasible_steps += 1;
+        if fsm_state.intercept_infeasible_steps <= config.intercept_cooldown_steps {
+            return ControlState::Intercept;
+        }
+        // Cooldown exhausted: fall through and let the normal priority order re-decide.
+    } else {
+        fsm_state.intercept_infeasible_steps = 0;
+    }
+
+    let candidate = if intruder_behind_defender(defender_pos, intruder_pos, protected_center) {
+        ControlState::Retreat
+    } else if interception_feasible {
+        ControlState::Intercept
+    } else if apollonian_circle.intersects(protected_zone) {
         ControlState::Engage
     } else {
-        // Priority 3: Move Apollonian circle towards goal circle
         ControlState::Travel
+    };
+
+    if candidate == fsm_state.control_state {
+        fsm_state.dwell_steps = 0;
+        return fsm_state.control_state.clone();
     }
+
+    fsm_state.dwell_steps += 1;
+    if fsm_state.dwell_steps >= config.dwell_steps.max(1) {
+        fsm_state.dwell_steps = 0;
+        fsm_state.control_state = candidate;
+    }
+    fsm_state.control_state.clone()
 }
 
-/// Calculate velocity for Travel state - simple vector from Apollonian center to goal center
+/// Calculate velocity for Travel state - vector from Apollonian center to goal center,
+/// using the "arrive" behavior to decelerate within `slowing_radius` of the goal.
 pub fn calculate_travel_velocity(
     apollonian_center: &Point,
     goal_center: &Point,
     max_speed: f64,
+    slowing_radius: f64,
+) -> Point {
+    calculate_arrive_velocity(apollonian_center, goal_center, max_speed, slowing_radius)
+}
+
+/// Scale the desired speed toward a target down linearly inside `slowing_radius`, so a
+/// defender decelerates onto the target instead of overshooting it.
+fn calculate_arrive_velocity(
+    current_pos: &Point,
+    target_pos: &Point,
+    max_speed: f64,
+    slowing_radius: f64,
 ) -> Point {
     let direction = Point::new(
-        goal_center.x - apollonian_center.x,
-        goal_center.y - apollonian_center.y,
+        target_pos.x - current_pos.x,
+        target_pos.y - current_pos.y,
     );
-    
+    let distance = direction.magnitude();
+
+    if distance < 1e-10 {
+        return Point::new(0.0, 0.0);
+    }
+
+    let desired_speed = if slowing_radius > 0.0 && distance < slowing_radius {
+        max_speed * (distance / slowing_radius)
+    } else {
+        max_speed
+    };
+
     let normalized = direction.normalize();
-    Point::new(
-        normalized.x * max_speed,
-        normalized.y * max_speed,
-    )
+    Point::new(normalized.x * desired_speed, normalized.y * desired_speed)
+}
+
+/// Limit how much a defender's velocity can change in one step, modeling inertia: the
+/// steering vector is clamped to `max_force`, then applied on top of the current velocity.
+fn calculate_steering(
+    desired_velocity: &Point,
+    current_velocity: &Point,
+    max_force: f64,
+    max_speed: f64,
+) -> Point {
+    let steering = Point::new(
+        desired_velocity.x - current_velocity.x,
+        desired_velocity.y - current_velocity.y,
+    );
+    let steering = clamp_velocity(&steering, max_force);
+
+    let new_velocity = Point::new(
+        current_velocity.x + steering.x,
+        current_velocity.y + steering.y,
+    );
+    clamp_velocity(&new_velocity, max_speed)
 }
 
 /// Calculate coverage arc length for a defender's Apollonian circle intersecting protected zone
@@ -59,84 +143,156 @@ pub fn calculate_coverage_arc(apollonian_circle: &Circle, protected_zone: &Circl
     calculate_arc_intersection_length(apollonian_circle, protected_zone)
 }
 
-/// Calculate overlap arc length between two defenders' coverage areas
+/// Determine the angular interval `(start, end)` (with `end` possibly `> 2π` to represent
+/// wraparound) on `zone`'s boundary that lies inside `circle`, about `zone.center`.
+///
+/// Handles the full-containment and disjoint cases directly; otherwise uses the two
+/// boundary crossing points to pick whichever of the two candidate arcs actually lies
+/// inside `circle` (by testing its midpoint), so the result is exact rather than a
+/// center-distance estimate.
+fn calculate_coverage_interval(circle: &Circle, zone: &Circle) -> Option<(f64, f64)> {
+    if !circle.intersects(zone) || circle.radius == f64::INFINITY {
+        return None;
+    }
+
+    let distance = circle.center.distance_to(&zone.center);
+
+    // The entire zone boundary lies inside the Apollonian circle.
+    if distance + zone.radius <= circle.radius {
+        return Some((0.0, 2.0 * PI));
+    }
+
+    // The Apollonian circle lies entirely inside the zone; it covers none of the zone's
+    // own boundary.
+    if distance + circle.radius <= zone.radius {
+        return None;
+    }
+
+    let points = circle_intersection_points(zone, circle);
+    if points.len() != 2 {
+        return None;
+    }
+
+    let normalize_angle = |angle: f64| {
+        let mut a = angle % (2.0 * PI);
+        if a < 0.0 {
+            a += 2.0 * PI;
+        }
+        a
+    };
+
+    let a1 = normalize_angle(zone.center.angle_to(&points[0]));
+    let a2 = normalize_angle(zone.center.angle_to(&points[1]));
+
+    // Length of the forward arc a1 -> a2, walking in the direction of increasing angle.
+    let forward_length = if a2 >= a1 { a2 - a1 } else { a2 + 2.0 * PI - a1 };
+    let mid_angle = normalize_angle(a1 + forward_length / 2.0);
+    let mid_point = Point::new(
+        zone.center.x + zone.radius * cos(mid_angle),
+        zone.center.y + zone.radius * sin(mid_angle),
+    );
+
+    if circle.contains_point(&mid_point) {
+        Some((a1, a1 + forward_length))
+    } else {
+        Some((a2, a2 + (2.0 * PI - forward_length)))
+    }
+}
+
+/// Split an angular interval `(start, end)` that may extend past `2π` into one or two
+/// sub-intervals, each contained within `[0, 2π)`.
+fn split_wrapping_interval(interval: (f64, f64)) -> Vec<(f64, f64)> {
+    let (start, end) = interval;
+    if end <= 2.0 * PI + 1e-9 {
+        vec![(start, end)]
+    } else {
+        vec![(start, 2.0 * PI), (0.0, end - 2.0 * PI)]
+    }
+}
+
+/// Length of the overlap between two angular intervals already confined to `[0, 2π)`.
+fn interval_overlap_length(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.1.min(b.1) - a.0.max(b.0)).max(0.0)
+}
+
+/// Calculate the overlap arc length between two defenders' coverage areas on the protected
+/// zone boundary.
+///
+/// This is exact geometry, not a center-distance heuristic: each defender's Apollonian
+/// circle is converted to the angular interval it covers on the zone's boundary, and the
+/// overlap is the length of the intersection of those two intervals (handling the `2π`
+/// wraparound by splitting intervals at the seam before intersecting).
 pub fn calculate_overlap_arc(
     defender1_circle: &Circle,
     defender2_circle: &Circle,
     protected_zone: &Circle,
 ) -> f64 {
-    // Find intersection points between the two Apollonian circles
-    let intersection_points = circle_intersection_points(defender1_circle, defender2_circle);
-    
-    if intersection_points.is_empty() {
-        return 0.0;
-    }
-    
-    // For each intersection point, check if it's inside the protected zone
-    let mut overlap_length = 0.0;
-    
-    // Simplified calculation: if circles intersect and both intersect protected zone,
-    // estimate overlap based on the smaller of the two coverage arcs
-    let coverage1 = calculate_coverage_arc(defender1_circle, protected_zone);
-    let coverage2 = calculate_coverage_arc(defender2_circle, protected_zone);
-    
-    if coverage1 > 0.0 && coverage2 > 0.0 {
-        // Estimate overlap as fraction of smaller coverage
-        let distance_between_centers = defender1_circle.center.distance_to(&defender2_circle.center);
-        let combined_radii = defender1_circle.radius + defender2_circle.radius;
-        
-        if distance_between_centers < combined_radii {
-            let overlap_fraction = (combined_radii - distance_between_centers) / combined_radii;
-            overlap_length = overlap_fraction * coverage1.min(coverage2);
+    let interval1 = match calculate_coverage_interval(defender1_circle, protected_zone) {
+        Some(interval) => interval,
+        None => return 0.0,
+    };
+    let interval2 = match calculate_coverage_interval(defender2_circle, protected_zone) {
+        Some(interval) => interval,
+        None => return 0.0,
+    };
+
+    let mut overlap_angle = 0.0;
+    for a in split_wrapping_interval(interval1) {
+        for b in split_wrapping_interval(interval2) {
+            overlap_angle += interval_overlap_length(a, b);
         }
     }
-    
-    overlap_length
+
+    protected_zone.radius * overlap_angle
 }
 
-/// Calculate loss function for Engage state
+/// Calculate loss function for Engage state.
+///
+/// `other_circles`/`grid` are the other defenders' Apollonian circles and the spatial-hash
+/// broadphase over them, built once by the caller from the step's unperturbed world state -
+/// `calculate_gradient` calls this four times per defender against only a perturbed copy of
+/// `defender_index`'s own position, so every other defender's circle (and thus the broadphase
+/// over them) is identical across those calls and across every other Engage defender in the
+/// same step. Rebuilding either per call would pay that cost again for no benefit.
 pub fn calculate_loss_engage(
     world_state: &WorldState,
     defender_index: usize,
     config: &SimConfig,
+    other_circles: &[Circle],
+    grid: &SpatialGrid,
 ) -> f64 {
     let defender = &world_state.defenders[defender_index];
-    
+
     // Calculate this defender's Apollonian circle
     let apollonian_circle = calculate_apollonian_circle(
         &defender.position,
         &world_state.intruder.position,
         config.speed_ratio(),
     );
-    
+
     // Calculate coverage arc (positive contribution)
     let coverage = calculate_coverage_arc(&apollonian_circle, &world_state.protected_zone);
-    
-    // Calculate overlap penalties (negative contribution)
+
+    // Calculate overlap penalties (negative contribution) against only the neighbors the
+    // spatial-hash broadphase flags as plausibly overlapping, so this stays cheap as the
+    // defender count grows instead of scanning every other defender.
     let mut overlap_penalty = 0.0;
-    
-    for (i, other_defender) in world_state.defenders.iter().enumerate() {
-        if i == defender_index {
+    for other_index in grid.neighbors_of(&apollonian_circle) {
+        if other_index == defender_index {
             continue;
         }
-        
-        let other_apollonian = calculate_apollonian_circle(
-            &other_defender.position,
-            &world_state.intruder.position,
-            config.speed_ratio(),
-        );
-        
+
         let overlap = calculate_overlap_arc(
             &apollonian_circle,
-            &other_apollonian,
+            &other_circles[other_index],
             &world_state.protected_zone,
         );
-        
+
         if overlap > config.epsilon {
             overlap_penalty += overlap - config.epsilon;
         }
     }
-    
+
     // Loss = w_repel * overlap_penalty - coverage
     // (We want to minimize this, so negative coverage is good)
     config.w_repel * overlap_penalty - coverage
@@ -148,82 +304,87 @@ pub fn calculate_gradient(
     defender_index: usize,
     config: &SimConfig,
     h: f64,
+    other_circles: &[Circle],
+    grid: &SpatialGrid,
 ) -> Point {
     let original_pos = world_state.defenders[defender_index].position.clone();
-    
+
     // Calculate gradient in x direction
     let mut world_state_x_plus = world_state.clone();
     world_state_x_plus.defenders[defender_index].position.x += h;
-    let loss_x_plus = calculate_loss_engage(&world_state_x_plus, defender_index, config);
-    
+    let loss_x_plus = calculate_loss_engage(&world_state_x_plus, defender_index, config, other_circles, grid);
+
     let mut world_state_x_minus = world_state.clone();
     world_state_x_minus.defenders[defender_index].position.x -= h;
-    let loss_x_minus = calculate_loss_engage(&world_state_x_minus, defender_index, config);
-    
+    let loss_x_minus = calculate_loss_engage(&world_state_x_minus, defender_index, config, other_circles, grid);
+
     let grad_x = (loss_x_plus - loss_x_minus) / (2.0 * h);
-    
+
     // Calculate gradient in y direction
     let mut world_state_y_plus = world_state.clone();
     world_state_y_plus.defenders[defender_index].position.y += h;
-    let loss_y_plus = calculate_loss_engage(&world_state_y_plus, defender_index, config);
-    
+    let loss_y_plus = calculate_loss_engage(&world_state_y_plus, defender_index, config, other_circles, grid);
+
     let mut world_state_y_minus = world_state.clone();
     world_state_y_minus.defenders[defender_index].position.y -= h;
-    let loss_y_minus = calculate_loss_engage(&world_state_y_minus, defender_index, config);
-    
+    let loss_y_minus = calculate_loss_engage(&world_state_y_minus, defender_index, config, other_circles, grid);
+
     let grad_y = (loss_y_plus - loss_y_minus) / (2.0 * h);
-    
+
     Point::new(grad_x, grad_y)
 }
 
-/// Calculate velocity for Engage state using gradient descent
+/// Calculate velocity for Engage state using gradient descent.
+///
+/// `other_circles`/`grid` are threaded down from `get_defender_velocity_commands_with_states`,
+/// which builds them once per simulation step and shares them across every Engage defender -
+/// see `calculate_loss_engage`.
 pub fn calculate_engage_velocity(
     world_state: &WorldState,
     defender_index: usize,
     config: &SimConfig,
+    other_circles: &[Circle],
+    grid: &SpatialGrid,
 ) -> Point {
     let h = 1e-4; // Small perturbation for numerical gradient
-    let gradient = calculate_gradient(world_state, defender_index, config, h);
-    
+    let gradient = calculate_gradient(world_state, defender_index, config, h, other_circles, grid);
+
     // Velocity is negative gradient scaled by learning rate
     let velocity = Point::new(
         -config.learning_rate * gradient.x,
         -config.learning_rate * gradient.y,
     );
-    
+
     // Clamp to maximum speed
     clamp_velocity(&velocity, config.defender_speed)
 }
 
-/// Calculate velocity for Intercept state - move directly toward interception point
+/// Calculate velocity for Intercept state - lead the intruder's predicted future position
+/// rather than chasing its current (stale) geometric crossing with the Apollonian circle,
+/// decelerating via "arrive" within `slowing_radius` of the interception point.
 pub fn calculate_intercept_velocity(
     defender_pos: &Point,
     apollonian_circle: &Circle,
     intruder_pos: &Point,
+    intruder_vel: &Point,
     protected_center: &Point,
     max_speed: f64,
+    slowing_radius: f64,
 ) -> Point {
-    // Recalculate interception point for accuracy
-    if let Some(target_point) = calculate_line_segment_circle_intersection(
-        intruder_pos,
-        protected_center,
-        apollonian_circle
-    ) {
-        // Direction vector from defender to interception point
-        let direction = Point::new(
-            target_point.x - defender_pos.x,
-            target_point.y - defender_pos.y,
-        );
-        
-        // Move at max speed toward the target
-        let normalized = direction.normalize();
-        Point::new(
-            normalized.x * max_speed,
-            normalized.y * max_speed,
-        )
-    } else {
-        // Fallback: if no intersection found, stop moving
-        Point::new(0.0, 0.0)
+    // Prefer the predictive swept-interception point, which accounts for intruder motion.
+    let target_point = calculate_interception_point(intruder_pos, intruder_vel, defender_pos, max_speed)
+        .map(|(point, _t)| point)
+        .or_else(|| {
+            // Fallback: static geometric crossing, e.g. for a stationary intruder.
+            calculate_line_segment_circle_intersection(intruder_pos, protected_center, apollonian_circle)
+        });
+
+    match target_point {
+        Some(target_point) => calculate_arrive_velocity(defender_pos, &target_point, max_speed, slowing_radius),
+        None => {
+            // No feasible interception: stop moving.
+            Point::new(0.0, 0.0)
+        }
     }
 }
 
@@ -241,57 +402,178 @@ pub fn clamp_velocity(velocity: &Point, max_speed: f64) -> Point {
     }
 }
 
+/// Predict a defender's position after one simulation step at its commanded velocity.
+fn predict_next_position(position: &Point, velocity: &Point, dt: f64) -> Point {
+    Point::new(position.x + velocity.x * dt, position.y + velocity.y * dt)
+}
+
+/// Resolve a predicted collision between two defenders by adjusting their velocities.
+///
+/// STOP mode cancels the pair's closing speed along the axis connecting them, leaving both
+/// defenders with the same velocity component along it. SLIDE mode removes only the
+/// approaching component, projecting onto the tangent so
+/// the defender slides around its neighbor. The correction is split reciprocally (half to
+/// each defender) so the pair doesn't oscillate.
+fn resolve_defender_collision(
+    pos_a: &Point,
+    vel_a: &Point,
+    pos_b: &Point,
+    vel_b: &Point,
+    mode: CollisionMode,
+) -> (Point, Point) {
+    let dx = pos_b.x - pos_a.x;
+    let dy = pos_b.y - pos_a.y;
+    let distance = sqrt(dx * dx + dy * dy);
+
+    if distance < 1e-10 {
+        // Coincident positions: nothing meaningful to project onto, leave velocities as-is.
+        return (vel_a.clone(), vel_b.clone());
+    }
+
+    let normal = Point::new(dx / distance, dy / distance); // points from A toward B
+
+    let a_n = vel_a.x * normal.x + vel_a.y * normal.y; // A's speed toward B
+    let b_n = vel_b.x * normal.x + vel_b.y * normal.y; // B's speed away from A
+
+    let (a_correction, b_correction) = match mode {
+        CollisionMode::Stop => {
+            let closing = (a_n - b_n).max(0.0);
+            (closing, -closing)
+        }
+        CollisionMode::Slide => (a_n.max(0.0), b_n.min(0.0)),
+    };
+
+    let new_vel_a = Point::new(
+        vel_a.x - 0.5 * a_correction * normal.x,
+        vel_a.y - 0.5 * a_correction * normal.y,
+    );
+    let new_vel_b = Point::new(
+        vel_b.x - 0.5 * b_correction * normal.x,
+        vel_b.y - 0.5 * b_correction * normal.y,
+    );
+
+    (new_vel_a, new_vel_b)
+}
+
+/// Post-process commanded velocities so no two defenders collide in the next step.
+///
+/// Predicts each defender's position after one step and, for any pair that would end up
+/// within `config.collision_radius`, resolves the collision per `config.collision_mode`.
+///
+/// Candidate pairs are first pruned via `collision::broad_phase_pairs` over each defender's
+/// predicted-position bounding circle, so the exact `distance_to` check below only runs on
+/// pairs whose broad bounds already overlap instead of every pair of defenders.
+fn avoid_defender_collisions(positions: &[Point], velocities: &mut Vec<Point>, config: &SimConfig) {
+    let predicted: Vec<Point> = positions
+        .iter()
+        .zip(velocities.iter())
+        .map(|(position, velocity)| predict_next_position(position, velocity, config.dt))
+        .collect();
+
+    let bounds: Vec<Circle> = predicted
+        .iter()
+        .map(|position| Circle::new(position.clone(), config.collision_radius))
+        .collect();
+
+    for (i, j) in collision::broad_phase_pairs(&bounds) {
+        if predicted[i].distance_to(&predicted[j]) < config.collision_radius {
+            let (new_vel_i, new_vel_j) = resolve_defender_collision(
+                &positions[i],
+                &velocities[i],
+                &positions[j],
+                &velocities[j],
+                config.collision_mode,
+            );
+            velocities[i] = new_vel_i;
+            velocities[j] = new_vel_j;
+        }
+    }
+}
+
 /// Main controller function - calculates velocity commands for all defenders
-/// Now supports three-state FSM with state persistence
+/// Supports the four-state FSM (Travel/Engage/Intercept/Retreat) with state persistence
 pub fn get_defender_velocity_commands_with_states(
     world_state: &WorldState,
-    defender_states: &mut Vec<ControlState>,
+    defender_states: &mut Vec<DefenderFsmState>,
     config: &SimConfig,
 ) -> Vec<Point> {
     let mut velocity_commands = Vec::new();
-    
+
     // Ensure we have enough states for all defenders
     while defender_states.len() < world_state.defenders.len() {
-        defender_states.push(ControlState::Travel);
+        defender_states.push(DefenderFsmState::new());
+    }
+
+    // Every defender's Apollonian circle, plus a spatial-hash broadphase over them, built once
+    // for the whole step and shared by every Engage defender's gradient descent below instead
+    // of each one rebuilding both per finite-difference sample - see `calculate_loss_engage`.
+    let apollonian_circles: Vec<Circle> = world_state
+        .defenders
+        .iter()
+        .map(|d| calculate_apollonian_circle(&d.position, &world_state.intruder.position, config.speed_ratio()))
+        .collect();
+
+    let cell_size = if world_state.protected_zone.radius.is_finite() && world_state.protected_zone.radius > 0.0 {
+        world_state.protected_zone.radius
+    } else {
+        1.0
+    };
+    let mut engage_grid = SpatialGrid::new(cell_size);
+    for (i, circle) in apollonian_circles.iter().enumerate() {
+        engage_grid.insert(i, circle);
     }
-    
+
     for (i, defender) in world_state.defenders.iter().enumerate() {
-        // Calculate Apollonian circle for this defender
-        let apollonian_circle = calculate_apollonian_circle(
-            &defender.position,
-            &world_state.intruder.position,
-            config.speed_ratio(),
-        );
-        
-        // Update state based on FSM transitions
-        defender_states[i] = determine_next_control_state(
-            &defender_states[i],
+        let apollonian_circle = apollonian_circles[i].clone();
+
+        // Update state based on FSM transitions (with dwell/cooldown hysteresis)
+        let control_state = determine_next_control_state(
+            &mut defender_states[i],
             &apollonian_circle,
             &world_state.protected_zone,
+            &defender.position,
             &world_state.intruder.position,
+            &world_state.intruder.velocity,
             &world_state.protected_zone.center,
+            config.defender_speed,
+            config,
         );
-        
-        // Calculate velocity based on current state
-        let velocity = match defender_states[i] {
-            ControlState::Travel => calculate_travel_velocity(
+
+        // Calculate desired velocity based on current state
+        let desired_velocity = match control_state {
+            ControlState::Travel | ControlState::Retreat => calculate_travel_velocity(
                 &apollonian_circle.center,
                 &world_state.protected_zone.center,
                 config.defender_speed,
+                config.slowing_radius,
             ),
-            ControlState::Engage => calculate_engage_velocity(world_state, i, config),
+            ControlState::Engage => calculate_engage_velocity(world_state, i, config, &apollonian_circles, &engage_grid),
             ControlState::Intercept => calculate_intercept_velocity(
                 &defender.position,
                 &apollonian_circle,
                 &world_state.intruder.position,
+                &world_state.intruder.velocity,
                 &world_state.protected_zone.center,
                 config.defender_speed,
+                config.slowing_radius,
             ),
         };
-        
+
+        // Force-limit the change in velocity so defenders don't jerk between states.
+        let velocity = calculate_steering(
+            &desired_velocity,
+            &defender.velocity,
+            config.max_force,
+            config.defender_speed,
+        );
+
         velocity_commands.push(velocity);
     }
-    
+
+    // Post-process so cooperative defenders don't drive into one another.
+    let positions: Vec<Point> = world_state.defenders.iter().map(|d| d.position.clone()).collect();
+    avoid_defender_collisions(&positions, &mut velocity_commands, config);
+
     velocity_commands
 }
 
@@ -300,7 +582,7 @@ pub fn get_defender_velocity_commands(
     world_state: &WorldState,
     config: &SimConfig,
 ) -> Vec<Point> {
-    let mut states = vec![ControlState::Travel; world_state.defenders.len()];
+    let mut states: Vec<DefenderFsmState> = (0..world_state.defenders.len()).map(|_| DefenderFsmState::new()).collect();
     get_defender_velocity_commands_with_states(world_state, &mut states, config)
 }
 
@@ -314,66 +596,363 @@ mod tests {
         let goal_center = Point::new(0.0, 0.0);
         let max_speed = 2.0;
         
-        let velocity = calculate_travel_velocity(&apollonian_center, &goal_center, max_speed);
+        let velocity = calculate_travel_velocity(&apollonian_center, &goal_center, max_speed, 1.0);
         
         assert!((velocity.magnitude() - max_speed).abs() < 1e-10);
         assert!(velocity.x < 0.0); // Should move toward goal (negative x)
         assert!(velocity.y.abs() < 1e-10); // Should be purely horizontal
     }
 
+    #[test]
+    fn test_travel_velocity_arrive_slows_inside_radius() {
+        let apollonian_center = Point::new(1.0, 0.0);
+        let goal_center = Point::new(0.0, 0.0);
+        let max_speed = 2.0;
+        let slowing_radius = 4.0; // distance (1.0) is inside the slowing radius
+
+        let velocity = calculate_travel_velocity(&apollonian_center, &goal_center, max_speed, slowing_radius);
+
+        // Desired speed should be scaled down to 1/4 of max within the slowing radius.
+        assert!((velocity.magnitude() - max_speed * 0.25).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_steering_is_force_limited() {
+        let desired = Point::new(10.0, 0.0);
+        let current = Point::new(0.0, 0.0);
+        let max_force = 0.5;
+        let max_speed = 10.0;
+
+        let velocity = calculate_steering(&desired, &current, max_force, max_speed);
+
+        // Velocity shouldn't jump straight to the desired velocity in one step.
+        assert!((velocity.magnitude() - max_force).abs() < 1e-10);
+    }
+
     #[test]
     fn test_control_state_determination() {
         let protected_zone = Circle::new(Point::new(0.0, 0.0), 2.0);
         let protected_center = Point::new(0.0, 0.0);
-        
-        // Test case 1: Interception opportunity (highest priority)
-        // Line from intruder to goal clearly intersects Apollonian circle
-        let intercepting_circle = Circle::new(Point::new(2.0, 0.0), 3.0);  
-        let intruder_pos = Point::new(6.0, 0.0);  // Intruder on x-axis, line passes through circle
-        
+        // dwell_steps = 1 so the first differing candidate fires immediately (hysteresis
+        // itself is covered in a dedicated test below).
+        let config = SimConfig::new(0.1, 2.0, 2.0, 1.0, 0.1, 10.0, 1.0, 1.0, 0.5, 1, 0, 0.0, 0.0);
+
+        // Test case 1: interception is feasible (highest priority) - slow intruder closing
+        // on a defender that's fast enough to cut it off.
+        let far_circle = Circle::new(Point::new(10.0, 0.0), 1.0);
+        let defender_pos = Point::new(5.0, 0.0);
+        let closing_intruder_pos = Point::new(8.0, 0.0);
+        let closing_intruder_vel = Point::new(-1.0, 0.0);
+
+        let mut fsm_state = DefenderFsmState::new();
         let intercept_state = determine_next_control_state(
-            &ControlState::Travel,  // Any current state
-            &intercepting_circle,
+            &mut fsm_state,
+            &far_circle,
             &protected_zone,
-            &intruder_pos,
-            &protected_center
+            &defender_pos,
+            &closing_intruder_pos,
+            &closing_intruder_vel,
+            &protected_center,
+            config.defender_speed,
+            &config,
         );
         assert_eq!(intercept_state, ControlState::Intercept);
-        
-        // Test case 2: No interception, circle intersects goal -> Engage
+
+        // Test case 2: interception infeasible, circle intersects goal -> Engage
         let intersecting_circle = Circle::new(Point::new(3.0, 0.0), 2.0);
-        let safe_intruder = Point::new(10.0, 10.0);  // Intruder not on direct path
-        
+        let safe_intruder_pos = Point::new(10.0, 10.0);
+        let fleeing_intruder_vel = Point::new(5.0, 5.0); // fast and receding - uncatchable
+
+        let mut fsm_state = DefenderFsmState::new();
         let engage_state = determine_next_control_state(
-            &ControlState::Travel,
+            &mut fsm_state,
             &intersecting_circle,
-            &protected_zone, 
-            &safe_intruder,
-            &protected_center
+            &protected_zone,
+            &defender_pos,
+            &safe_intruder_pos,
+            &fleeing_intruder_vel,
+            &protected_center,
+            config.defender_speed,
+            &config,
         );
         assert_eq!(engage_state, ControlState::Engage);
-        
-        // Test case 3: No interception, no goal intersection -> Travel
-        let far_circle = Circle::new(Point::new(10.0, 0.0), 1.0);
-        
+
+        // Test case 3: interception infeasible, no goal intersection -> Travel
+        let mut fsm_state = DefenderFsmState::new();
         let travel_state = determine_next_control_state(
-            &ControlState::Travel,
+            &mut fsm_state,
             &far_circle,
             &protected_zone,
-            &safe_intruder,
-            &protected_center
+            &defender_pos,
+            &safe_intruder_pos,
+            &fleeing_intruder_vel,
+            &protected_center,
+            config.defender_speed,
+            &config,
         );
         assert_eq!(travel_state, ControlState::Travel);
-        
-        // Test case 4: Intercept state is terminal
-        let stay_intercept = determine_next_control_state(
-            &ControlState::Intercept,
-            &far_circle,  // Doesn't matter - should stay Intercept
+
+        // Test case 4: intruder has slipped past the defender relative to the zone -> Retreat
+        let breached_defender_pos = Point::new(5.0, 0.0);
+        let past_intruder_pos = Point::new(1.0, 0.0); // closer to the zone than the defender
+
+        let mut fsm_state = DefenderFsmState::new();
+        let retreat_state = determine_next_control_state(
+            &mut fsm_state,
+            &far_circle,
+            &protected_zone,
+            &breached_defender_pos,
+            &past_intruder_pos,
+            &fleeing_intruder_vel,
+            &protected_center,
+            config.defender_speed,
+            &config,
+        );
+        assert_eq!(retreat_state, ControlState::Retreat);
+    }
+
+    #[test]
+    fn test_control_state_dwell_suppresses_chatter() {
+        let protected_zone = Circle::new(Point::new(0.0, 0.0), 2.0);
+        let protected_center = Point::new(0.0, 0.0);
+        let config = SimConfig::new(0.1, 2.0, 2.0, 1.0, 0.1, 10.0, 1.0, 1.0, 0.5, 3, 0, 0.0, 0.0);
+
+        let far_circle = Circle::new(Point::new(10.0, 0.0), 1.0);
+        let defender_pos = Point::new(5.0, 0.0);
+        let safe_intruder_pos = Point::new(10.0, 10.0);
+        let fleeing_intruder_vel = Point::new(5.0, 5.0);
+
+        let mut fsm_state = DefenderFsmState::new(); // starts at Travel
+        let intersecting_circle = Circle::new(Point::new(3.0, 0.0), 2.0);
+
+        // The Engage-triggering condition must hold for dwell_steps=3 calls before it fires.
+        for _ in 0..2 {
+            let state = determine_next_control_state(
+                &mut fsm_state,
+                &intersecting_circle,
+                &protected_zone,
+                &defender_pos,
+                &safe_intruder_pos,
+                &fleeing_intruder_vel,
+                &protected_center,
+                config.defender_speed,
+                &config,
+            );
+            assert_eq!(state, ControlState::Travel);
+        }
+
+        let state = determine_next_control_state(
+            &mut fsm_state,
+            &intersecting_circle,
+            &protected_zone,
+            &defender_pos,
+            &safe_intruder_pos,
+            &fleeing_intruder_vel,
+            &protected_center,
+            config.defender_speed,
+            &config,
+        );
+        assert_eq!(state, ControlState::Engage);
+
+        // Unrelated: the far circle's lack of intersection shouldn't matter to this test.
+        let _ = far_circle;
+    }
+
+    #[test]
+    fn test_intercept_decommits_after_cooldown() {
+        let protected_zone = Circle::new(Point::new(0.0, 0.0), 2.0);
+        let protected_center = Point::new(0.0, 0.0);
+        let config = SimConfig::new(0.1, 2.0, 2.0, 1.0, 0.1, 10.0, 1.0, 1.0, 0.5, 1, 2, 0.0, 0.0);
+
+        let far_circle = Circle::new(Point::new(10.0, 0.0), 1.0);
+        let defender_pos = Point::new(5.0, 0.0);
+        let closing_intruder_pos = Point::new(8.0, 0.0);
+        let closing_intruder_vel = Point::new(-1.0, 0.0);
+        let fleeing_intruder_vel = Point::new(5.0, 5.0); // suddenly uncatchable
+
+        let mut fsm_state = DefenderFsmState::new();
+        let state = determine_next_control_state(
+            &mut fsm_state,
+            &far_circle,
+            &protected_zone,
+            &defender_pos,
+            &closing_intruder_pos,
+            &closing_intruder_vel,
+            &protected_center,
+            config.defender_speed,
+            &config,
+        );
+        assert_eq!(state, ControlState::Intercept);
+
+        // Interception becomes infeasible, but should stay committed through the cooldown.
+        for _ in 0..2 {
+            let state = determine_next_control_state(
+                &mut fsm_state,
+                &far_circle,
+                &protected_zone,
+                &defender_pos,
+                &closing_intruder_pos,
+                &fleeing_intruder_vel,
+                &protected_center,
+                config.defender_speed,
+                &config,
+            );
+            assert_eq!(state, ControlState::Intercept);
+        }
+
+        // Cooldown exhausted: should de-commit back to a non-Intercept state.
+        let state = determine_next_control_state(
+            &mut fsm_state,
+            &far_circle,
             &protected_zone,
-            &safe_intruder,
-            &protected_center
+            &defender_pos,
+            &closing_intruder_pos,
+            &fleeing_intruder_vel,
+            &protected_center,
+            config.defender_speed,
+            &config,
         );
-        assert_eq!(stay_intercept, ControlState::Intercept);
+        assert_ne!(state, ControlState::Intercept);
+    }
+
+    #[test]
+    fn test_overlap_arc_self_overlap_equals_coverage() {
+        let protected_zone = Circle::new(Point::new(0.0, 0.0), 5.0);
+        let circle = Circle::new(Point::new(5.0, 0.0), 5.0);
+
+        let coverage = calculate_coverage_arc(&circle, &protected_zone);
+        let overlap = calculate_overlap_arc(&circle, &circle, &protected_zone);
+
+        assert!((overlap - coverage).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_overlap_arc_disjoint_defenders_no_overlap() {
+        let protected_zone = Circle::new(Point::new(0.0, 0.0), 5.0);
+        // Symmetric circles covering opposite sides of the zone boundary.
+        let circle1 = Circle::new(Point::new(5.0, 0.0), 5.0);
+        let circle2 = Circle::new(Point::new(-5.0, 0.0), 5.0);
+
+        let overlap = calculate_overlap_arc(&circle1, &circle2, &protected_zone);
+        assert!(overlap.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_loss_engage_penalizes_overlap_with_shared_grid() {
+        let config = SimConfig::new(
+            0.1, 2.0, 4.0, 1.0, 0.1, 10.0, 1.0, 1.0, 0.5, 1, 0, 0.0, 0.0,
+        );
+        let intruder = AgentState::new(Point::new(10.0, 0.0), Point::new(-1.0, 0.0));
+        let protected_zone = Circle::new(Point::new(0.0, 0.0), 2.0);
+
+        // Two defenders close enough together that their Apollonian circles overlap.
+        let close = WorldState::new(
+            vec![
+                AgentState::new(Point::new(-3.0, 0.0), Point::new(0.0, 0.0)),
+                AgentState::new(Point::new(-3.0, 0.5), Point::new(0.0, 0.0)),
+            ],
+            intruder.clone(),
+            protected_zone.clone(),
+        );
+        let close_circles: Vec<Circle> = close
+            .defenders
+            .iter()
+            .map(|d| calculate_apollonian_circle(&d.position, &close.intruder.position, config.speed_ratio()))
+            .collect();
+        let mut close_grid = SpatialGrid::new(protected_zone.radius);
+        for (i, circle) in close_circles.iter().enumerate() {
+            close_grid.insert(i, circle);
+        }
+        let close_loss = calculate_loss_engage(&close, 0, &config, &close_circles, &close_grid);
+
+        // Same setup, but the second defender is far enough away that the circles can't overlap.
+        let far = WorldState::new(
+            vec![
+                AgentState::new(Point::new(-3.0, 0.0), Point::new(0.0, 0.0)),
+                AgentState::new(Point::new(-3.0, 500.0), Point::new(0.0, 0.0)),
+            ],
+            intruder,
+            protected_zone.clone(),
+        );
+        let far_circles: Vec<Circle> = far
+            .defenders
+            .iter()
+            .map(|d| calculate_apollonian_circle(&d.position, &far.intruder.position, config.speed_ratio()))
+            .collect();
+        let mut far_grid = SpatialGrid::new(protected_zone.radius);
+        for (i, circle) in far_circles.iter().enumerate() {
+            far_grid.insert(i, circle);
+        }
+        let far_loss = calculate_loss_engage(&far, 0, &config, &far_circles, &far_grid);
+
+        assert!(close_loss > far_loss);
+    }
+
+    #[test]
+    fn test_get_defender_velocity_commands_with_states_engage_uses_shared_grid() {
+        let config = SimConfig::new(
+            0.1, 2.0, 4.0, 1.0, 0.1, 10.0, 1.0, 1.0, 0.5, 1, 0, 0.0, 0.0,
+        );
+        let world_state = WorldState::new(
+            vec![
+                AgentState::new(Point::new(-3.0, 0.0), Point::new(0.0, 0.0)),
+                AgentState::new(Point::new(-3.0, 0.5), Point::new(0.0, 0.0)),
+            ],
+            AgentState::new(Point::new(10.0, 0.0), Point::new(-1.0, 0.0)),
+            Circle::new(Point::new(0.0, 0.0), 2.0),
+        );
+        let mut states = vec![DefenderFsmState::new(); world_state.defenders.len()];
+
+        let velocities = get_defender_velocity_commands_with_states(&world_state, &mut states, &config);
+
+        assert_eq!(velocities.len(), 2);
+        for velocity in &velocities {
+            assert!(velocity.magnitude() <= config.defender_speed + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_resolve_collision_stop_zeroes_closing_component() {
+        // Two defenders on the x-axis, both driving straight at each other.
+        let pos_a = Point::new(0.0, 0.0);
+        let vel_a = Point::new(1.0, 0.0);
+        let pos_b = Point::new(2.0, 0.0);
+        let vel_b = Point::new(-1.0, 0.0);
+
+        let (new_a, new_b) = resolve_defender_collision(&pos_a, &vel_a, &pos_b, &vel_b, CollisionMode::Stop);
+
+        // Closing component along the connecting axis should be fully neutralized.
+        assert!(new_a.x.abs() < 1e-10);
+        assert!(new_b.x.abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_resolve_collision_slide_preserves_receding_defender() {
+        // A approaches B, but B is already moving away - slide should leave B untouched.
+        let pos_a = Point::new(0.0, 0.0);
+        let vel_a = Point::new(1.0, 0.0);
+        let pos_b = Point::new(2.0, 0.0);
+        let vel_b = Point::new(1.0, 0.0); // moving further away from A
+
+        let (new_a, new_b) = resolve_defender_collision(&pos_a, &vel_a, &pos_b, &vel_b, CollisionMode::Slide);
+
+        assert!((new_b.x - vel_b.x).abs() < 1e-10); // untouched, already receding
+        assert!(new_a.x < vel_a.x); // A's approach is damped
+    }
+
+    #[test]
+    fn test_avoid_defender_collisions_resolves_predicted_overlap() {
+        // Unmodified, one dt = 1.0 step would leave them 0.2 apart, inside collision_radius.
+        let positions = vec![Point::new(0.0, 0.0), Point::new(2.2, 0.0)];
+        let mut velocities = vec![Point::new(1.0, 0.0), Point::new(-1.0, 0.0)];
+        let mut config = SimConfig::new(0.1, 2.0, 2.0, 1.0, 0.1, 10.0, 1.0, 1.0, 0.5, 1, 0, 0.0, 0.0);
+        config.collision_mode = CollisionMode::Stop;
+
+        avoid_defender_collisions(&positions, &mut velocities, &config);
+
+        let next_a = predict_next_position(&positions[0], &velocities[0], config.dt);
+        let next_b = predict_next_position(&positions[1], &velocities[1], config.dt);
+        assert!(next_a.distance_to(&next_b) >= config.collision_radius - 1e-10);
     }
 
     #[test]
@@ -1,4 +1,6 @@
-use crate::structs::{Point, Circle};
+use crate::ops::{acos, cos, sin, sqrt, FloatPow};
+use crate::structs::{Arc, Point, Circle};
+use std::collections::{HashMap, HashSet};
 use std::f64::consts::PI;
 
 /// Calculate the Apollonian circle for a defender and intruder.
@@ -28,7 +30,7 @@ pub fn calculate_apollonian_circle(
     // Vector from defender to intruder
     let dx = intruder_pos.x - defender_pos.x;
     let dy = intruder_pos.y - defender_pos.y;
-    let d = (dx * dx + dy * dy).sqrt();
+    let d = sqrt(dx * dx + dy * dy);
     
     // Unit vector from defender toward intruder
     let ux = dx / d;
@@ -95,12 +97,12 @@ pub fn calculate_arc_intersection_length(circle1: &Circle, circle2: &Circle) ->
     }
 
     // Calculate the central angle of the intersection arc using law of cosines
-    let cos_half_angle = (circle1.radius.powi(2) + distance.powi(2) - circle2.radius.powi(2)) 
+    let cos_half_angle = (circle1.radius.squared() + distance.squared() - circle2.radius.squared())
         / (2.0 * circle1.radius * distance);
-    
+
     // Clamp to valid range for acos
     let cos_half_angle = cos_half_angle.max(-1.0).min(1.0);
-    let half_angle = cos_half_angle.acos();
+    let half_angle = acos(cos_half_angle);
     let full_angle = 2.0 * half_angle;
 
     circle1.radius * full_angle
@@ -124,8 +126,8 @@ pub fn circle_intersection_points(circle1: &Circle, circle2: &Circle) -> Vec<Poi
         return vec![];
     }
 
-    let a = (circle1.radius.powi(2) - circle2.radius.powi(2) + d.powi(2)) / (2.0 * d);
-    let h = (circle1.radius.powi(2) - a.powi(2)).sqrt();
+    let a = (circle1.radius.squared() - circle2.radius.squared() + d.squared()) / (2.0 * d);
+    let h = sqrt(circle1.radius.squared() - a.squared());
     
     // Point on line between centers
     let p = Point::new(
@@ -151,6 +153,257 @@ pub fn circle_intersection_points(circle1: &Circle, circle2: &Circle) -> Vec<Poi
     vec![intersection1, intersection2]
 }
 
+/// Exact coverage of `reference`'s boundary by the union of `circles` (e.g. defenders'
+/// Apollonian circles), correct even when a covering arc crosses the `0`/`2*PI` seam -
+/// unlike `calculate_arc_intersection_length`, which only handles a single pair and assumes
+/// no wraparound.
+///
+/// For each circle: find its intersection points with `reference` and convert them to
+/// angles on `reference`; the two points cut `reference`'s boundary into two arcs, and the
+/// one whose midpoint lies inside the covering circle is the covered interval (split at the
+/// seam if it wraps). A circle with no intersection points either contains `reference`
+/// entirely (full `[0, 2*PI)` coverage) or lies entirely on one side of it (no coverage,
+/// whether disjoint or itself contained within `reference`).
+///
+/// Returns the total covered arc length (in the same units as `reference.radius`) and the
+/// disjoint angular gaps - the stretches of `reference`'s boundary no circle covers.
+pub fn calculate_boundary_coverage(reference: &Circle, circles: &[Circle]) -> (f64, Vec<(f64, f64)>) {
+    let report = calculate_boundary_coverage_report(reference, circles);
+    (report.covered_length, report.gaps)
+}
+
+/// Covered angular intervals, uncovered gaps, and total covered length of `reference`'s
+/// boundary under the union of `circles` - the full result behind [`calculate_boundary_coverage`]
+/// (which only surfaces the covered length and the gaps), kept as its own type so callers doing
+/// multi-defender placement can also ask "is the defense airtight" ([`BoundaryCoverageReport::sealed`])
+/// and "where's the worst gap" ([`BoundaryCoverageReport::largest_gap`]) without recomputing
+/// anything.
+#[derive(Debug, Clone)]
+pub struct BoundaryCoverageReport {
+    /// Total arc length of `reference`'s boundary covered by at least one circle.
+    pub covered_length: f64,
+    /// Merged, non-overlapping covered intervals on `[0, 2*PI)`, sorted by start angle.
+    pub covered_intervals: Vec<(f64, f64)>,
+    /// The complementary uncovered intervals on `[0, 2*PI)`, sorted by start angle.
+    pub gaps: Vec<(f64, f64)>,
+}
+
+impl BoundaryCoverageReport {
+    /// True when no gap remains, i.e. every point of the boundary is covered by some circle.
+    pub fn sealed(&self) -> bool {
+        self.gaps.is_empty()
+    }
+
+    /// The widest uncovered interval, the likeliest next spot for an intruder to slip through -
+    /// a natural gradient target for placing another defender. `None` when `sealed()`.
+    pub fn largest_gap(&self) -> Option<(f64, f64)> {
+        self.gaps
+            .iter()
+            .copied()
+            .max_by(|a, b| (a.1 - a.0).partial_cmp(&(b.1 - b.0)).unwrap_or(std::cmp::Ordering::Equal))
+    }
+}
+
+/// Exact coverage of `reference`'s boundary by the union of `circles` (e.g. defenders'
+/// Apollonian circles), correct even when a covering arc crosses the `0`/`2*PI` seam -
+/// unlike `calculate_arc_intersection_length`, which only handles a single pair and assumes
+/// no wraparound.
+///
+/// For each circle: find its intersection points with `reference` and convert them to
+/// angles on `reference`; the two points cut `reference`'s boundary into two arcs, and the
+/// one whose midpoint lies inside the covering circle is the covered interval (split at the
+/// seam if it wraps). A circle with no intersection points either contains `reference`
+/// entirely (full `[0, 2*PI)` coverage) or lies entirely on one side of it (no coverage,
+/// whether disjoint or itself contained within `reference`).
+///
+/// Returns the merged covered intervals, the complementary uncovered gaps, and the total
+/// covered arc length (in the same units as `reference.radius`); fully-contained and
+/// fully-disjoint defenders contribute the whole circle or nothing respectively.
+pub fn calculate_boundary_coverage_report(reference: &Circle, circles: &[Circle]) -> BoundaryCoverageReport {
+    let normalize = |angle: f64| {
+        let mut a = angle % (2.0 * PI);
+        if a < 0.0 {
+            a += 2.0 * PI;
+        }
+        a
+    };
+
+    let mut covering_arcs: Vec<Arc> = Vec::new();
+
+    for circle in circles {
+        let points = circle_intersection_points(reference, circle);
+
+        if points.len() < 2 {
+            let distance = reference.center.distance_to(&circle.center);
+            // Reference lies entirely inside the covering circle: the whole boundary is covered.
+            if distance + reference.radius <= circle.radius {
+                covering_arcs.push(Arc::new(0.0, 2.0 * PI));
+            }
+            // Otherwise the circle is disjoint from, or entirely inside, reference - no coverage.
+            continue;
+        }
+
+        let angle_a = normalize(reference.center.angle_to(&points[0]));
+        let angle_b = normalize(reference.center.angle_to(&points[1]));
+
+        // The two points split the boundary into arcs [angle_a, angle_b) and [angle_b, angle_a);
+        // whichever one's midpoint sits inside the covering circle is the covered interval.
+        let mid_forward = Arc::new(angle_a, angle_b).length() / 2.0 + angle_a;
+        let forward_midpoint = Point::new(
+            reference.center.x + reference.radius * cos(mid_forward),
+            reference.center.y + reference.radius * sin(mid_forward),
+        );
+
+        let (start, end) = if circle.contains_point(&forward_midpoint) {
+            (angle_a, angle_b)
+        } else {
+            (angle_b, angle_a)
+        };
+
+        covering_arcs.push(Arc::new(start, end));
+    }
+
+    let covered_arcs = Arc::union(&covering_arcs);
+    let covered_length: f64 = covered_arcs.iter().map(|arc| arc.length() * reference.radius).sum();
+
+    let mut covered_intervals: Vec<(f64, f64)> = covered_arcs
+        .iter()
+        .flat_map(|arc| arc.normalized_segments())
+        .collect();
+    covered_intervals.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut gaps = Vec::new();
+    let mut cursor = 0.0;
+    for (start, end) in &covered_intervals {
+        if *start > cursor + 1e-9 {
+            gaps.push((cursor, *start));
+        }
+        cursor = cursor.max(*end);
+    }
+    if cursor < 2.0 * PI - 1e-9 {
+        gaps.push((cursor, 2.0 * PI));
+    }
+
+    BoundaryCoverageReport {
+        covered_length,
+        covered_intervals,
+        gaps,
+    }
+}
+
+/// Calculate the earliest point at which a defender can intercept a moving intruder.
+///
+/// The intruder is assumed to move at constant velocity `intruder_vel` from `intruder_pos`.
+/// We solve for the smallest non-negative `t` at which a defender moving at `defender_speed`
+/// from `defender_pos` can reach the intruder's future position, i.e. the smallest root of
+/// `|intruder_pos + intruder_vel*t - defender_pos|^2 = (defender_speed*t)^2`:
+///   a*t^2 + b*t + c = 0
+///   a = |intruder_vel|^2 - defender_speed^2
+///   b = 2*(intruder_pos - defender_pos)·intruder_vel
+///   c = |intruder_pos - defender_pos|^2
+///
+/// Returns the predicted interception point and the time to reach it, or `None` if the
+/// defender can never catch the intruder (discriminant negative, or both roots negative).
+pub fn calculate_interception_point(
+    intruder_pos: &Point,
+    intruder_vel: &Point,
+    defender_pos: &Point,
+    defender_speed: f64,
+) -> Option<(Point, f64)> {
+    let dx = intruder_pos.x - defender_pos.x;
+    let dy = intruder_pos.y - defender_pos.y;
+
+    let a = intruder_vel.x * intruder_vel.x + intruder_vel.y * intruder_vel.y
+        - defender_speed * defender_speed;
+    let b = 2.0 * (dx * intruder_vel.x + dy * intruder_vel.y);
+    let c = dx * dx + dy * dy;
+
+    let t = if a.abs() < 1e-10 {
+        // Degenerate to the linear case b*t + c = 0.
+        if b.abs() < 1e-10 {
+            return None;
+        }
+        let t = -c / b;
+        if t < 0.0 {
+            return None;
+        }
+        t
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let sqrt_discriminant = sqrt(discriminant);
+        let t1 = (-b - sqrt_discriminant) / (2.0 * a);
+        let t2 = (-b + sqrt_discriminant) / (2.0 * a);
+
+        let mut candidates: Vec<f64> = [t1, t2].into_iter().filter(|t| *t >= 0.0).collect();
+        if candidates.is_empty() {
+            return None;
+        }
+        candidates.sort_by(|x, y| x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal));
+        candidates[0]
+    };
+
+    let interception_point = Point::new(
+        intruder_pos.x + intruder_vel.x * t,
+        intruder_pos.y + intruder_vel.y * t,
+    );
+
+    Some((interception_point, t))
+}
+
+/// Calculate the earliest time `t` in `[0, 1]` at which two circles moving at constant
+/// velocity over one timestep first touch (continuous swept collision / time-of-impact).
+///
+/// Solves `|Δp + tΔv|^2 = R^2` for the squared distance between centers, where
+/// `Δp = c1.center - c2.center`, `Δv = v1 - v2`, and `R = c1.radius + c2.radius`:
+///   (Δv·Δv)*t^2 + 2*(Δp·Δv)*t + (Δp·Δp - R^2) = 0
+///
+/// Returns `0.0` if the circles already overlap at `t = 0`, `None` if they never touch
+/// within the step (discriminant negative, smallest root outside `[0, 1]`, or the circles
+/// aren't actually approaching each other, i.e. `a ≈ 0`).
+pub fn calculate_circle_circle_toi(
+    c1: &Circle,
+    v1: &Point,
+    c2: &Circle,
+    v2: &Point,
+) -> Option<f64> {
+    let dpx = c1.center.x - c2.center.x;
+    let dpy = c1.center.y - c2.center.y;
+    let dvx = v1.x - v2.x;
+    let dvy = v1.y - v2.y;
+    let r = c1.radius + c2.radius;
+
+    if dpx * dpx + dpy * dpy <= r * r {
+        return Some(0.0);
+    }
+
+    let a = dvx * dvx + dvy * dvy;
+    if a.abs() < 1e-10 {
+        return None;
+    }
+    let b = 2.0 * (dpx * dvx + dpy * dvy);
+    let c = dpx * dpx + dpy * dpy - r * r;
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let sqrt_discriminant = sqrt(discriminant);
+    let t1 = (-b - sqrt_discriminant) / (2.0 * a);
+    let t2 = (-b + sqrt_discriminant) / (2.0 * a);
+
+    let mut candidates: Vec<f64> = [t1, t2].into_iter().filter(|t| *t >= 0.0 && *t <= 1.0).collect();
+    if candidates.is_empty() {
+        return None;
+    }
+    candidates.sort_by(|x, y| x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal));
+    Some(candidates[0])
+}
+
 /// Calculate intersection between a line segment and a circle.
 /// Returns the closest intersection point to p1 (start of segment) if one exists.
 /// This is used to determine if the intruder's path intersects a defender's Apollonian circle.
@@ -187,7 +440,7 @@ pub fn calculate_line_segment_circle_intersection(
         return None; // Degenerate case: p1 == p2
     }
     
-    let sqrt_discriminant = discriminant.sqrt();
+    let sqrt_discriminant = sqrt(discriminant);
     let t1 = (-b - sqrt_discriminant) / (2.0 * a);
     let t2 = (-b + sqrt_discriminant) / (2.0 * a);
     
@@ -213,6 +466,162 @@ pub fn calculate_line_segment_circle_intersection(
     Some(valid_intersections[0].1.clone())
 }
 
+/// A finite line segment, e.g. a wall or terrain edge that blocks both movement and sight.
+/// Not exposed to Python; `WorldState::obstacles` is populated from Rust like
+/// `forbidden_regions`.
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub a: Point,
+    pub b: Point,
+}
+
+impl Segment {
+    pub fn new(a: Point, b: Point) -> Self {
+        Segment { a, b }
+    }
+
+    /// Where `self` and `other` cross, if anywhere within both segments' own extents.
+    ///
+    /// Writes each segment parametrically as `self.a + t*r` and `other.a + u*s` and solves
+    /// `self.a + t*r = other.a + u*s` via the standard 2D cross-product test: letting
+    /// `denom = r x s`, `t = (other.a - self.a) x s / denom` and `u = (other.a - self.a) x r /
+    /// denom`. Parallel (including collinear) segments make `denom` zero and have no single
+    /// crossing point; otherwise the segments actually intersect - not just the infinite
+    /// lines through them - only when both `t` and `u` land in `[0, 1]`.
+    pub fn intersects(&self, other: &Segment) -> Option<Point> {
+        let r = Point::new(self.b.x - self.a.x, self.b.y - self.a.y);
+        let s = Point::new(other.b.x - other.a.x, other.b.y - other.a.y);
+        let denom = r.x * s.y - r.y * s.x;
+
+        if denom.abs() < 1e-12 {
+            return None;
+        }
+
+        let dx = other.a.x - self.a.x;
+        let dy = other.a.y - self.a.y;
+        let t = (dx * s.y - dy * s.x) / denom;
+        let u = (dx * r.y - dy * r.x) / denom;
+
+        if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+            Some(Point::new(self.a.x + t * r.x, self.a.y + t * r.y))
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether the straight sight line from `from` to `to` is unobstructed by any of `obstacles` -
+/// true iff it crosses none of them. Lets a consumer of `calculate_apollonian_circle` (e.g.
+/// `generate_threat_map`) withhold a defender's threat contribution from cells its Apollonian
+/// region covers geometrically but a wall actually hides from it.
+pub fn has_line_of_sight(from: &Point, to: &Point, obstacles: &[Segment]) -> bool {
+    let sight = Segment::new(from.clone(), to.clone());
+    obstacles.iter().all(|obstacle| sight.intersects(obstacle).is_none())
+}
+
+/// Uniform-grid broadphase over circles, keyed by the cells their bounding box covers.
+///
+/// Buckets each inserted circle into every cell its bounding box touches, then
+/// `candidate_pairs` returns only the id pairs that share a cell - a cheap prefilter so a
+/// downstream pairwise check (Apollonian overlap, arc coverage) doesn't have to scan every
+/// defender pair as the swarm grows. Infinite-radius circles (e.g. an equal-speed Apollonian
+/// "circle" degenerating to a line) have no finite bounding box, so they're tracked
+/// separately and paired against every other inserted id.
+pub struct SpatialGrid {
+    cell_size: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    unbounded: Vec<usize>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f64) -> Self {
+        SpatialGrid {
+            cell_size,
+            cells: HashMap::new(),
+            unbounded: Vec::new(),
+        }
+    }
+
+    pub fn insert(&mut self, id: usize, circle: &Circle) {
+        if circle.radius == f64::INFINITY || self.cell_size <= 0.0 {
+            self.unbounded.push(id);
+            return;
+        }
+
+        let min_x = ((circle.center.x - circle.radius) / self.cell_size).floor() as i64;
+        let max_x = ((circle.center.x + circle.radius) / self.cell_size).floor() as i64;
+        let min_y = ((circle.center.y - circle.radius) / self.cell_size).floor() as i64;
+        let max_y = ((circle.center.y + circle.radius) / self.cell_size).floor() as i64;
+
+        for cx in min_x..=max_x {
+            for cy in min_y..=max_y {
+                self.cells.entry((cx, cy)).or_insert_with(Vec::new).push(id);
+            }
+        }
+    }
+
+    /// Ids that share at least one grid cell with `circle` (plus every unbounded id), without
+    /// materializing the full `candidate_pairs` set first - a cheaper way to ask "who's near
+    /// this one circle" than enumerating every pair in the grid and filtering down to the
+    /// ones that mention it.
+    pub fn neighbors_of(&self, circle: &Circle) -> Vec<usize> {
+        let mut neighbors: HashSet<usize> = self.unbounded.iter().copied().collect();
+
+        if circle.radius == f64::INFINITY || self.cell_size <= 0.0 {
+            neighbors.extend(self.cells.values().flatten().copied());
+            return neighbors.into_iter().collect();
+        }
+
+        let min_x = ((circle.center.x - circle.radius) / self.cell_size).floor() as i64;
+        let max_x = ((circle.center.x + circle.radius) / self.cell_size).floor() as i64;
+        let min_y = ((circle.center.y - circle.radius) / self.cell_size).floor() as i64;
+        let max_y = ((circle.center.y + circle.radius) / self.cell_size).floor() as i64;
+
+        for cx in min_x..=max_x {
+            for cy in min_y..=max_y {
+                if let Some(ids) = self.cells.get(&(cx, cy)) {
+                    neighbors.extend(ids.iter().copied());
+                }
+            }
+        }
+
+        neighbors.into_iter().collect()
+    }
+
+    /// Distinct id pairs that share at least one grid cell, plus every pair involving an
+    /// unbounded circle. Order within a pair is not significant.
+    pub fn candidate_pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs: HashSet<(usize, usize)> = HashSet::new();
+
+        for ids in self.cells.values() {
+            for i in 0..ids.len() {
+                for j in (i + 1)..ids.len() {
+                    pairs.insert(ordered_pair(ids[i], ids[j]));
+                }
+            }
+        }
+
+        if !self.unbounded.is_empty() {
+            let mut all_ids: HashSet<usize> = self.cells.values().flatten().copied().collect();
+            all_ids.extend(self.unbounded.iter().copied());
+
+            for &u in &self.unbounded {
+                for &other in &all_ids {
+                    if other != u {
+                        pairs.insert(ordered_pair(u, other));
+                    }
+                }
+            }
+        }
+
+        pairs.into_iter().collect()
+    }
+}
+
+fn ordered_pair(a: usize, b: usize) -> (usize, usize) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,6 +662,100 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_interception_point_moving_intruder() {
+        // Intruder crosses the origin moving in +x at speed 1, defender starts on the
+        // y-axis and is twice as fast, so it should be able to cut the corner.
+        let intruder_pos = Point::new(-4.0, 0.0);
+        let intruder_vel = Point::new(1.0, 0.0);
+        let defender_pos = Point::new(0.0, 3.0);
+        let defender_speed = 2.0;
+
+        let result = calculate_interception_point(&intruder_pos, &intruder_vel, &defender_pos, defender_speed);
+        assert!(result.is_some());
+
+        let (point, t) = result.unwrap();
+        assert!(t >= 0.0);
+        // Defender must be able to reach the point in exactly t at its max speed.
+        let reach_dist = defender_pos.distance_to(&point);
+        assert!((reach_dist - defender_speed * t).abs() < 1e-6);
+        // Intruder must be at that point at time t.
+        assert!((intruder_pos.x + intruder_vel.x * t - point.x).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_interception_point_unreachable() {
+        // Intruder is much faster than the defender and running straight away.
+        let intruder_pos = Point::new(0.0, 0.0);
+        let intruder_vel = Point::new(10.0, 0.0);
+        let defender_pos = Point::new(-5.0, 0.0);
+        let defender_speed = 1.0;
+
+        let result = calculate_interception_point(&intruder_pos, &intruder_vel, &defender_pos, defender_speed);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_circle_circle_toi_converging() {
+        // Two unit circles 3 units apart on the x-axis, closing at relative speed 2: centers
+        // need to close from 3 to 2 (sum of radii), i.e. 1 unit at relative speed 2 -> t=0.5.
+        let c1 = Circle::new(Point::new(-1.5, 0.0), 1.0);
+        let v1 = Point::new(1.0, 0.0);
+        let c2 = Circle::new(Point::new(1.5, 0.0), 1.0);
+        let v2 = Point::new(-1.0, 0.0);
+
+        let toi = calculate_circle_circle_toi(&c1, &v1, &c2, &v2);
+        assert!(toi.is_some());
+        assert!((toi.unwrap() - 0.5).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_circle_circle_toi_already_overlapping() {
+        let c1 = Circle::new(Point::new(0.0, 0.0), 2.0);
+        let v1 = Point::new(0.0, 0.0);
+        let c2 = Circle::new(Point::new(1.0, 0.0), 2.0);
+        let v2 = Point::new(0.0, 0.0);
+
+        let toi = calculate_circle_circle_toi(&c1, &v1, &c2, &v2);
+        assert_eq!(toi, Some(0.0));
+    }
+
+    #[test]
+    fn test_circle_circle_toi_never_touches() {
+        // Moving apart, never close the gap within the step.
+        let c1 = Circle::new(Point::new(-5.0, 0.0), 1.0);
+        let v1 = Point::new(-1.0, 0.0);
+        let c2 = Circle::new(Point::new(5.0, 0.0), 1.0);
+        let v2 = Point::new(1.0, 0.0);
+
+        let toi = calculate_circle_circle_toi(&c1, &v1, &c2, &v2);
+        assert!(toi.is_none());
+    }
+
+    #[test]
+    fn test_spatial_grid_finds_nearby_pair_and_skips_distant_one() {
+        let mut grid = SpatialGrid::new(2.0);
+        grid.insert(0, &Circle::new(Point::new(0.0, 0.0), 0.5));
+        grid.insert(1, &Circle::new(Point::new(1.0, 0.0), 0.5)); // shares a cell with 0
+        grid.insert(2, &Circle::new(Point::new(100.0, 100.0), 0.5)); // far away
+
+        let pairs = grid.candidate_pairs();
+        assert!(pairs.contains(&(0, 1)));
+        assert!(!pairs.iter().any(|&(a, b)| a == 2 || b == 2));
+    }
+
+    #[test]
+    fn test_spatial_grid_pairs_unbounded_circle_with_everything() {
+        let mut grid = SpatialGrid::new(2.0);
+        grid.insert(0, &Circle::new(Point::new(0.0, 0.0), 0.5));
+        grid.insert(1, &Circle::new(Point::new(100.0, 100.0), 0.5));
+        grid.insert(2, &Circle::new(Point::new(0.0, 0.0), f64::INFINITY));
+
+        let pairs = grid.candidate_pairs();
+        assert!(pairs.contains(&(0, 2)) || pairs.contains(&(2, 0)));
+        assert!(pairs.contains(&(1, 2)) || pairs.contains(&(2, 1)));
+    }
+
     #[test]
     fn test_line_segment_circle_intersection() {
         let circle = Circle::new(Point::new(0.0, 0.0), 2.0);
@@ -288,4 +791,143 @@ mod tests {
         assert!((point2.x - 2.0).abs() < 1e-10);
         assert!(point2.y.abs() < 1e-10);
     }
+
+    #[test]
+    fn test_boundary_coverage_single_defender_leaves_a_gap() {
+        let reference = Circle::new(Point::new(0.0, 0.0), 10.0);
+        // Overlaps the reference boundary near angle 0 but doesn't fully surround it.
+        let defender = Circle::new(Point::new(12.0, 0.0), 5.0);
+
+        let (covered, gaps) = calculate_boundary_coverage(&reference, &[defender]);
+
+        assert!(covered > 0.0 && covered < 2.0 * PI * reference.radius);
+        assert!(!gaps.is_empty());
+        // Covered length plus gap lengths should reconstruct the full circumference.
+        let gap_length: f64 = gaps.iter().map(|(s, e)| (e - s) * reference.radius).sum();
+        assert!((covered + gap_length - 2.0 * PI * reference.radius).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_boundary_coverage_full_when_defender_contains_reference() {
+        let reference = Circle::new(Point::new(0.0, 0.0), 2.0);
+        let defender = Circle::new(Point::new(0.0, 0.0), 10.0);
+
+        let (covered, gaps) = calculate_boundary_coverage(&reference, &[defender]);
+
+        assert!((covered - 2.0 * PI * reference.radius).abs() < 1e-9);
+        assert!(gaps.is_empty());
+    }
+
+    #[test]
+    fn test_boundary_coverage_empty_when_defender_inside_reference() {
+        let reference = Circle::new(Point::new(0.0, 0.0), 10.0);
+        let defender = Circle::new(Point::new(0.0, 0.0), 2.0);
+
+        let (covered, gaps) = calculate_boundary_coverage(&reference, &[defender]);
+
+        assert!(covered.abs() < 1e-9);
+        assert_eq!(gaps.len(), 1);
+        assert!((gaps[0].1 - gaps[0].0 - 2.0 * PI).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_boundary_coverage_handles_arc_that_wraps_past_the_seam() {
+        let reference = Circle::new(Point::new(0.0, 0.0), 10.0);
+        // Centered on the +x axis (angle 0), so its covered arc straddles the 0/2*PI seam.
+        let defender = Circle::new(Point::new(15.0, 0.0), 7.0);
+
+        let (covered, gaps) = calculate_boundary_coverage(&reference, &[defender]);
+
+        assert!(covered > 0.0 && covered < 2.0 * PI * reference.radius);
+        // The seam itself (angle 0) must be covered, not reported as a gap.
+        assert!(gaps.iter().all(|(start, end)| !(*start < 1e-9 && *end > 2.0 * PI - 1e-9)));
+        let gap_length: f64 = gaps.iter().map(|(s, e)| (e - s) * reference.radius).sum();
+        assert!((covered + gap_length - 2.0 * PI * reference.radius).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_boundary_coverage_report_sealed_with_fully_surrounding_defenders() {
+        let reference = Circle::new(Point::new(0.0, 0.0), 10.0);
+        // Three defenders 120 degrees apart, each wide enough to overlap both neighbors'
+        // covered arcs and seal the reference boundary with no gap.
+        let defenders = vec![
+            Circle::new(Point::new(5.0, 0.0), 9.0),
+            Circle::new(Point::new(-2.5, 4.330127), 9.0),
+            Circle::new(Point::new(-2.5, -4.330127), 9.0),
+        ];
+
+        let report = calculate_boundary_coverage_report(&reference, &defenders);
+
+        assert!(report.sealed());
+        assert!(report.largest_gap().is_none());
+        assert!((report.covered_length - 2.0 * PI * reference.radius).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_boundary_coverage_report_largest_gap_picks_the_widest_one() {
+        let reference = Circle::new(Point::new(0.0, 0.0), 10.0);
+        // Two narrow defenders near angle 0 and angle PI, both tilted above the x-axis, leaving
+        // a wide gap near -PI/2 and a narrower one near PI/2.
+        let defenders = vec![
+            Circle::new(Point::new(10.0, 1.5), 2.0),
+            Circle::new(Point::new(-10.0, 1.0), 2.0),
+        ];
+
+        let report = calculate_boundary_coverage_report(&reference, &defenders);
+
+        assert!(!report.sealed());
+        let (start, end) = report.largest_gap().expect("defenders don't seal the boundary");
+        let widest = report
+            .gaps
+            .iter()
+            .map(|(s, e)| e - s)
+            .fold(0.0_f64, f64::max);
+        assert!(((end - start) - widest).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_boundary_coverage_and_report_agree() {
+        let reference = Circle::new(Point::new(0.0, 0.0), 10.0);
+        let defender = Circle::new(Point::new(12.0, 0.0), 5.0);
+
+        let (covered, gaps) = calculate_boundary_coverage(&reference, &[defender]);
+        let report = calculate_boundary_coverage_report(&reference, &[defender]);
+
+        assert!((covered - report.covered_length).abs() < 1e-12);
+        assert_eq!(gaps, report.gaps);
+    }
+
+    #[test]
+    fn test_segment_intersects_crossing_segment() {
+        let a = Segment::new(Point::new(0.0, -1.0), Point::new(0.0, 1.0));
+        let b = Segment::new(Point::new(-1.0, 0.0), Point::new(1.0, 0.0));
+
+        let point = a.intersects(&b);
+        assert!(point.is_some());
+        let point = point.unwrap();
+        assert!(point.x.abs() < 1e-10 && point.y.abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_segment_intersects_none_when_disjoint_or_parallel() {
+        let a = Segment::new(Point::new(0.0, 0.0), Point::new(1.0, 0.0));
+        let b = Segment::new(Point::new(2.0, 0.0), Point::new(3.0, 0.0)); // Collinear, no overlap.
+        assert!(a.intersects(&b).is_none());
+
+        let c = Segment::new(Point::new(0.0, 1.0), Point::new(1.0, 1.0)); // Parallel, offset.
+        assert!(a.intersects(&c).is_none());
+
+        let d = Segment::new(Point::new(5.0, -1.0), Point::new(5.0, 1.0)); // Crosses the line, not the segment.
+        assert!(a.intersects(&d).is_none());
+    }
+
+    #[test]
+    fn test_has_line_of_sight_blocked_by_wall_between() {
+        let from = Point::new(-5.0, 0.0);
+        let to = Point::new(5.0, 0.0);
+        let wall = Segment::new(Point::new(0.0, -2.0), Point::new(0.0, 2.0));
+
+        assert!(!has_line_of_sight(&from, &to, &[wall]));
+        assert!(has_line_of_sight(&from, &to, &[]));
+    }
 }
\ No newline at end of file
@@ -1,14 +1,19 @@
 use pyo3::prelude::*;
 
+mod ops;
 mod structs;
 mod geometry;
 mod controller;
 mod pathfinding;
+mod intruder_policy;
+mod collision;
 
 use structs::*;
 use geometry::*;
 use controller::*;
 use pathfinding::*;
+use intruder_policy::*;
+use collision::{Polygon, Capsule, Shape};
 
 /// Python module exports
 #[pymodule]
@@ -16,19 +21,31 @@ fn interception_core(_py: Python, m: &PyModule) -> PyResult<()> {
     // Export data structures
     m.add_class::<Point>()?;
     m.add_class::<Circle>()?;
+    m.add_class::<RayHit>()?;
+    m.add_class::<Annulus>()?;
     m.add_class::<AgentState>()?;
     m.add_class::<WorldState>()?;
     m.add_class::<SimConfig>()?;
-    m.add_class::<ControlState>()?;
+    m.add_class::<DefenderFsmState>()?;
     m.add_class::<GridConfig>()?;
     m.add_class::<GridNode>()?;
     m.add_class::<PathResult>()?;
-    
+    m.add_class::<VisibilityPathResult>()?;
+    m.add_class::<IntruderPathCache>()?;
+    m.add_class::<Polygon>()?;
+    m.add_class::<Capsule>()?;
+
     // Export geometry functions
     m.add_function(wrap_pyfunction!(py_calculate_apollonian_circle, m)?)?;
     m.add_function(wrap_pyfunction!(py_calculate_arc_intersection_length, m)?)?;
     m.add_function(wrap_pyfunction!(py_circle_intersection_points, m)?)?;
-    
+
+    // Export collision functions
+    m.add_function(wrap_pyfunction!(py_circle_polygon_separation, m)?)?;
+    m.add_function(wrap_pyfunction!(py_circle_polygon_overlaps, m)?)?;
+    m.add_function(wrap_pyfunction!(py_circle_capsule_separation, m)?)?;
+    m.add_function(wrap_pyfunction!(py_circle_capsule_overlaps, m)?)?;
+
     // Export controller functions
     m.add_function(wrap_pyfunction!(py_get_defender_velocity_commands, m)?)?;
     m.add_function(wrap_pyfunction!(py_get_defender_velocity_commands_with_states, m)?)?;
@@ -37,10 +54,16 @@ fn interception_core(_py: Python, m: &PyModule) -> PyResult<()> {
     // Export pathfinding functions
     m.add_function(wrap_pyfunction!(py_calculate_intruder_next_position, m)?)?;
     m.add_function(wrap_pyfunction!(py_calculate_intruder_full_path, m)?)?;
+    m.add_function(wrap_pyfunction!(py_calculate_intruder_next_position_with_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(py_calculate_intruder_full_path_with_cache, m)?)?;
     m.add_function(wrap_pyfunction!(py_generate_threat_map, m)?)?;
     m.add_function(wrap_pyfunction!(py_to_grid_coords, m)?)?;
     m.add_function(wrap_pyfunction!(py_to_world_coords, m)?)?;
-    
+    m.add_function(wrap_pyfunction!(py_visibility_graph_pathfind, m)?)?;
+
+    // Export intruder policy functions
+    m.add_function(wrap_pyfunction!(py_compute_intruder_velocity, m)?)?;
+
     Ok(())
 }
 
@@ -66,6 +89,32 @@ fn py_circle_intersection_points(circle1: &Circle, circle2: &Circle) -> Vec<Poin
     circle_intersection_points(circle1, circle2)
 }
 
+/// Python wrapper for collision::separation between a `Circle` (e.g. an agent body) and a
+/// `Polygon` (e.g. a wall or keep-out zone).
+#[pyfunction]
+fn py_circle_polygon_separation(circle: &Circle, polygon: &Polygon) -> Option<(Point, f64)> {
+    collision::separation(&Shape::Circle(circle.clone()), &Shape::Polygon(polygon.clone()))
+}
+
+/// Python wrapper for collision::overlaps between a `Circle` and a `Polygon`.
+#[pyfunction]
+fn py_circle_polygon_overlaps(circle: &Circle, polygon: &Polygon) -> bool {
+    collision::overlaps(&Shape::Circle(circle.clone()), &Shape::Polygon(polygon.clone()))
+}
+
+/// Python wrapper for collision::separation between a `Circle` and a `Capsule` (e.g. an
+/// elongated vehicle body).
+#[pyfunction]
+fn py_circle_capsule_separation(circle: &Circle, capsule: &Capsule) -> Option<(Point, f64)> {
+    collision::separation(&Shape::Circle(circle.clone()), &Shape::Capsule(capsule.clone()))
+}
+
+/// Python wrapper for collision::overlaps between a `Circle` and a `Capsule`.
+#[pyfunction]
+fn py_circle_capsule_overlaps(circle: &Circle, capsule: &Capsule) -> bool {
+    collision::overlaps(&Shape::Circle(circle.clone()), &Shape::Capsule(capsule.clone()))
+}
+
 /// Python wrapper for get_defender_velocity_commands (legacy)
 #[pyfunction]
 fn py_get_defender_velocity_commands(
@@ -80,9 +129,9 @@ fn py_get_defender_velocity_commands(
 #[pyfunction]
 fn py_get_defender_velocity_commands_with_states(
     world_state: &WorldState,
-    defender_states: Vec<ControlState>,
+    defender_states: Vec<DefenderFsmState>,
     config: &SimConfig,
-) -> (Vec<Point>, Vec<ControlState>) {
+) -> (Vec<Point>, Vec<DefenderFsmState>) {
     let mut states = defender_states;
     let velocities = get_defender_velocity_commands_with_states(world_state, &mut states, config);
     (velocities, states)
@@ -118,6 +167,36 @@ fn py_calculate_intruder_full_path(
     calculate_intruder_full_path(world_state, grid_config, sim_config)
 }
 
+/// Python wrapper for calculate_intruder_next_position_with_cache.
+/// Returns both the next position and the updated cache for the caller to pass back in on
+/// the following tick.
+#[pyfunction]
+fn py_calculate_intruder_next_position_with_cache(
+    world_state: &WorldState,
+    grid_config: &GridConfig,
+    sim_config: &SimConfig,
+    cache: IntruderPathCache,
+) -> (Option<Point>, IntruderPathCache) {
+    let mut cache = cache;
+    let next_position = calculate_intruder_next_position_with_cache(world_state, grid_config, sim_config, &mut cache);
+    (next_position, cache)
+}
+
+/// Python wrapper for calculate_intruder_full_path_with_cache.
+/// Returns both the path and the updated cache for the caller to pass back in on the
+/// following tick.
+#[pyfunction]
+fn py_calculate_intruder_full_path_with_cache(
+    world_state: &WorldState,
+    grid_config: &GridConfig,
+    sim_config: &SimConfig,
+    cache: IntruderPathCache,
+) -> (PathResult, IntruderPathCache) {
+    let mut cache = cache;
+    let path_result = calculate_intruder_full_path_with_cache(world_state, grid_config, sim_config, &mut cache);
+    (path_result, cache)
+}
+
 /// Python wrapper for generate_threat_map
 #[pyfunction]
 fn py_generate_threat_map(
@@ -140,6 +219,22 @@ fn py_to_world_coords(node: &GridNode, config: &GridConfig) -> Point {
     to_world_coords(node, config)
 }
 
+/// Python wrapper for visibility_graph_pathfind
+#[pyfunction]
+fn py_visibility_graph_pathfind(
+    start: &Point,
+    goal: &Point,
+    obstacles: Vec<Circle>,
+) -> VisibilityPathResult {
+    visibility_graph_pathfind(start, goal, &obstacles)
+}
+
+/// Python wrapper for compute_intruder_velocity
+#[pyfunction]
+fn py_compute_intruder_velocity(world_state: &WorldState, config: &SimConfig) -> Point {
+    compute_intruder_velocity(world_state, config)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,6 +264,14 @@ mod tests {
             4.0,   // intruder_speed
             1.0,   // w_repel
             0.1,   // epsilon
+            10.0,  // max_force
+            1.0,   // slowing_radius
+            1.0,   // dt
+            0.5,   // collision_radius
+            1,     // dwell_steps
+            0,     // intercept_cooldown_steps
+            0.0,   // zigzag_amplitude
+            0.0,   // zigzag_wavelength
         );
         
         let velocities = get_defender_velocity_commands(&world_state, &config);
@@ -0,0 +1,102 @@
+//! Centralizes the handful of transcendental math calls used across the simulation behind a
+//! `libm` Cargo feature, so regression scenarios can be made bit-reproducible across
+//! platforms and Rust versions by swapping in a fixed software implementation instead of
+//! relying on the platform's libm, whose rounding for `sqrt`/`acos`/`atan2` isn't guaranteed
+//! identical everywhere. Default (feature disabled) behavior is unchanged: plain `std`.
+
+#[cfg(not(feature = "libm"))]
+pub fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+
+#[cfg(feature = "libm")]
+pub fn sqrt(x: f64) -> f64 {
+    libm::sqrt(x)
+}
+
+#[cfg(not(feature = "libm"))]
+pub fn acos(x: f64) -> f64 {
+    x.acos()
+}
+
+#[cfg(feature = "libm")]
+pub fn acos(x: f64) -> f64 {
+    libm::acos(x)
+}
+
+#[cfg(not(feature = "libm"))]
+pub fn atan2(y: f64, x: f64) -> f64 {
+    y.atan2(x)
+}
+
+#[cfg(feature = "libm")]
+pub fn atan2(y: f64, x: f64) -> f64 {
+    libm::atan2(y, x)
+}
+
+#[cfg(not(feature = "libm"))]
+pub fn cos(x: f64) -> f64 {
+    x.cos()
+}
+
+#[cfg(feature = "libm")]
+pub fn cos(x: f64) -> f64 {
+    libm::cos(x)
+}
+
+#[cfg(not(feature = "libm"))]
+pub fn sin(x: f64) -> f64 {
+    x.sin()
+}
+
+#[cfg(feature = "libm")]
+pub fn sin(x: f64) -> f64 {
+    libm::sin(x)
+}
+
+/// Replaces `f64::powi`, whose codegen (and thus exact rounding) isn't guaranteed stable
+/// across Rust versions, with explicit multiplication.
+pub trait FloatPow {
+    fn squared(self) -> f64;
+}
+
+impl FloatPow for f64 {
+    fn squared(self) -> f64 {
+        self * self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt_matches_std() {
+        assert!((sqrt(4.0) - 2.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_acos_matches_std() {
+        assert!((acos(1.0) - 0.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_atan2_matches_std() {
+        assert!((atan2(1.0, 1.0) - std::f64::consts::FRAC_PI_4).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_cos_matches_std() {
+        assert!((cos(0.0) - 1.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_sin_matches_std() {
+        assert!((sin(0.0) - 0.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_float_pow_squared() {
+        assert!((3.0_f64.squared() - 9.0).abs() < 1e-10);
+    }
+}
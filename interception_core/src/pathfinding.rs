@@ -1,7 +1,10 @@
-use crate::structs::{Point, Circle, GridConfig, GridNode, PathResult, WorldState, SimConfig};
-use crate::geometry::{calculate_apollonian_circle, to_grid_coords, to_world_coords, is_valid_grid_position};
+use crate::structs::{Point, Circle, GridConfig, GridNode, PathResult, PathfindingMode, Region, VisibilityPathResult, WorldState, SimConfig};
+use crate::geometry::{calculate_apollonian_circle, calculate_boundary_coverage, has_line_of_sight, to_grid_coords, to_world_coords, is_valid_grid_position, Segment};
+use crate::ops::{acos, cos, sin, sqrt};
+use pyo3::prelude::*;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::cmp::Ordering;
+use std::f64::consts::PI;
 
 /// A* node for pathfinding with cost tracking and parent relationships
 #[derive(Debug, Clone, PartialEq)]
@@ -36,7 +39,7 @@ pub fn generate_threat_map(
 ) -> Vec<Vec<f64>> {
     // Initialize cost map with base costs
     let mut cost_map = vec![vec![grid_config.base_cost; grid_config.width]; grid_config.height];
-    
+
     // Add threat penalty for each defender's Apollonian circle
     for defender in &world_state.defenders {
         let apollonian_circle = calculate_apollonian_circle(
@@ -44,57 +47,120 @@ pub fn generate_threat_map(
             &world_state.intruder.position,
             sim_config.speed_ratio(),
         );
-        
+
         // Skip infinite radius circles
         if apollonian_circle.radius == f64::INFINITY {
             continue;
         }
-        
-        mark_circle_threat(&mut cost_map, &apollonian_circle, grid_config);
+
+        mark_circle_threat(&mut cost_map, &apollonian_circle, &defender.position, &world_state.obstacles, grid_config);
     }
-    
+
+    // Forbidden regions block movement outright, just like walls - mark them before walls so
+    // a wall segment drawn inside a forbidden region still wins if the two ever disagree.
+    mark_forbidden_regions(&mut cost_map, &world_state.forbidden_regions, grid_config);
+
+    // Walls block movement outright, regardless of any defender's threat - mark them last so
+    // they always win over a threat penalty laid down on the same cell.
+    mark_obstacle_walls(&mut cost_map, &world_state.obstacles, grid_config);
+
     cost_map
 }
 
-/// Mark grid cells within a circle as high-threat areas
+/// Mark grid cells within a circle as high-threat areas, skipping any cell a wall hides from
+/// the defender - its Apollonian region covers the cell geometrically, but the defender could
+/// never actually reach it without passing through the wall.
 fn mark_circle_threat(
     cost_map: &mut Vec<Vec<f64>>,
     circle: &Circle,
+    defender_pos: &Point,
+    obstacles: &[Segment],
     grid_config: &GridConfig,
 ) {
     // Convert circle center to grid coordinates for efficient bounding
     let center_grid = to_grid_coords(&circle.center, grid_config);
-    
+
     if center_grid.is_none() {
         return; // Circle center is outside grid bounds
     }
-    
+
     let center_grid = center_grid.unwrap();
-    
+
     // Calculate grid radius (conservative estimate)
     let (min_x, max_x, min_y, max_y) = grid_config.world_bounds;
     let cell_size = ((max_x - min_x) / grid_config.width as f64)
         .max((max_y - min_y) / grid_config.height as f64);
     let grid_radius = (circle.radius / cell_size).ceil() as usize;
-    
+
     // Check cells in a square around the circle center
     let start_row = center_grid.row.saturating_sub(grid_radius);
     let end_row = (center_grid.row + grid_radius + 1).min(grid_config.height);
     let start_col = center_grid.col.saturating_sub(grid_radius);
     let end_col = (center_grid.col + grid_radius + 1).min(grid_config.width);
-    
+
     for row in start_row..end_row {
         for col in start_col..end_col {
             let node = GridNode::new(row, col);
             let world_pos = to_world_coords(&node, grid_config);
-            
-            if circle.contains_point(&world_pos) {
+
+            if circle.contains_point(&world_pos) && has_line_of_sight(defender_pos, &world_pos, obstacles) {
                 cost_map[row][col] += grid_config.threat_penalty;
             }
         }
     }
 }
 
+/// Mark every grid cell inside any `forbidden_regions` entry as impassable (`f64::INFINITY`).
+///
+/// `Region` is an arbitrary CSG combination (union/intersection/difference/translate), so
+/// unlike a defender's Apollonian circle there's no cheap bounding box to restrict the scan
+/// to - this checks `contains_point` against every cell in the grid.
+fn mark_forbidden_regions(
+    cost_map: &mut Vec<Vec<f64>>,
+    forbidden_regions: &[Region],
+    grid_config: &GridConfig,
+) {
+    if forbidden_regions.is_empty() {
+        return;
+    }
+
+    for row in 0..grid_config.height {
+        for col in 0..grid_config.width {
+            let node = GridNode::new(row, col);
+            let world_pos = to_world_coords(&node, grid_config);
+
+            if forbidden_regions.iter().any(|region| region.contains_point(&world_pos)) {
+                cost_map[row][col] = f64::INFINITY;
+            }
+        }
+    }
+}
+
+/// Mark every grid cell an obstacle segment passes through as impassable (`f64::INFINITY`),
+/// via the same supercover traversal `line_of_sight` uses for Theta* shortcuts - so a wall
+/// can't be slipped past by a diagonal step that only grazes its corner.
+fn mark_obstacle_walls(
+    cost_map: &mut Vec<Vec<f64>>,
+    obstacles: &[Segment],
+    grid_config: &GridConfig,
+) {
+    for obstacle in obstacles {
+        let start = to_grid_coords(&obstacle.a, grid_config);
+        let end = to_grid_coords(&obstacle.b, grid_config);
+
+        let (start, end) = match (start, end) {
+            (Some(start), Some(end)) => (start, end),
+            _ => continue, // Obstacle endpoint falls outside the grid entirely.
+        };
+
+        for (cell, _) in supercover_cells(&start, &end) {
+            if is_valid_grid_position(cell.row, cell.col, grid_config) {
+                cost_map[cell.row][cell.col] = f64::INFINITY;
+            }
+        }
+    }
+}
+
 /// Manhattan distance heuristic for 4-connected grid
 fn manhattan_distance(a: &GridNode, b: &GridNode) -> f64 {
     ((a.row as i32 - b.row as i32).abs() + (a.col as i32 - b.col as i32).abs()) as f64
@@ -223,203 +289,1471 @@ pub fn astar_pathfind(
     PathResult::new(vec![], 0.0, false)
 }
 
-/// Calculate the next position for the intruder using A* pathfinding
-pub fn calculate_intruder_next_position(
-    world_state: &WorldState,
-    grid_config: &GridConfig,
-    sim_config: &SimConfig,
-) -> Option<Point> {
-    // Generate threat map
-    let cost_map = generate_threat_map(world_state, grid_config, sim_config);
-    
-    // Convert positions to grid coordinates
-    let start_grid = to_grid_coords(&world_state.intruder.position, grid_config)?;
-    
-    // Find the best goal target within the protected zone
-    let goal_target = find_best_goal_target(world_state, grid_config, &cost_map)?;
-    
-    // Run A* pathfinding to the best goal target
-    let path_result = astar_pathfind(&start_grid, &goal_target, &cost_map, grid_config);
-    
-    if !path_result.found || path_result.path.len() < 2 {
-        return None; // No path found or already at goal
-    }
-    
-    // Return the next step in the path (convert back to world coordinates)
-    let next_grid_pos = &path_result.path[1];
-    Some(to_world_coords(next_grid_pos, grid_config))
+/// Euclidean distance heuristic/step cost for the 8-connected Theta* grid.
+fn euclidean_distance(a: &GridNode, b: &GridNode) -> f64 {
+    let dr = a.row as f64 - b.row as f64;
+    let dc = a.col as f64 - b.col as f64;
+    sqrt(dr * dr + dc * dc)
 }
 
-/// Find the best goal target within the protected zone circle
-fn find_best_goal_target(
-    world_state: &WorldState,
-    grid_config: &GridConfig,
-    cost_map: &Vec<Vec<f64>>,
-) -> Option<GridNode> {
-    let protected_zone = &world_state.protected_zone;
-    
-    // If intruder is already in goal zone, return current position
-    if protected_zone.contains_point(&world_state.intruder.position) {
-        return to_grid_coords(&world_state.intruder.position, grid_config);
-    }
-    
-    // Find all grid cells within the protected zone
-    let mut goal_candidates = Vec::new();
-    
-    // Calculate grid bounds around the protected zone
-    let zone_center_grid = to_grid_coords(&protected_zone.center, grid_config)?;
-    let (min_x, max_x, min_y, max_y) = grid_config.world_bounds;
-    let cell_size = ((max_x - min_x) / grid_config.width as f64)
-        .max((max_y - min_y) / grid_config.height as f64);
-    let grid_radius = (protected_zone.radius / cell_size).ceil() as usize + 1;
-    
-    let start_row = zone_center_grid.row.saturating_sub(grid_radius);
-    let end_row = (zone_center_grid.row + grid_radius + 1).min(grid_config.height);
-    let start_col = zone_center_grid.col.saturating_sub(grid_radius);
-    let end_col = (zone_center_grid.col + grid_radius + 1).min(grid_config.width);
-    
-    for row in start_row..end_row {
-        for col in start_col..end_col {
-            let node = GridNode::new(row, col);
-            let world_pos = to_world_coords(&node, grid_config);
-            
-            // Check if this grid cell is within the protected zone
-            if protected_zone.contains_point(&world_pos) {
-                goal_candidates.push((node, cost_map[row][col]));
+/// Get valid neighbors for an 8-connected grid (Moore neighborhood), letting Theta*'s
+/// straightened paths cut diagonally instead of only stepping axis-aligned.
+fn get_neighbors_8(node: &GridNode, grid_config: &GridConfig) -> Vec<GridNode> {
+    let mut neighbors = Vec::new();
+    let directions = [
+        (0i32, 1i32), (0, -1), (1, 0), (-1, 0),
+        (1, 1), (1, -1), (-1, 1), (-1, -1),
+    ];
+
+    for (dr, dc) in directions {
+        let new_row = node.row as i32 + dr;
+        let new_col = node.col as i32 + dc;
+
+        if new_row >= 0 && new_col >= 0 {
+            let new_row = new_row as usize;
+            let new_col = new_col as usize;
+
+            if is_valid_grid_position(new_row, new_col, grid_config) {
+                neighbors.push(GridNode::new(new_row, new_col));
             }
         }
     }
-    
-    if goal_candidates.is_empty() {
-        // Fallback to center if no candidates found
-        return to_grid_coords(&protected_zone.center, grid_config);
-    }
-    
-    // Select the goal candidate with the lowest cost (safest path)
-    goal_candidates.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
-    Some(goal_candidates[0].0.clone())
-}
 
-/// Get the full path for visualization purposes
-pub fn calculate_intruder_full_path(
-    world_state: &WorldState,
-    grid_config: &GridConfig,
-    sim_config: &SimConfig,
-) -> PathResult {
-    let cost_map = generate_threat_map(world_state, grid_config, sim_config);
-    
-    let start_grid = match to_grid_coords(&world_state.intruder.position, grid_config) {
-        Some(pos) => pos,
-        None => return PathResult::new(vec![], 0.0, false),
-    };
-    
-    let goal_target = match find_best_goal_target(world_state, grid_config, &cost_map) {
-        Some(target) => target,
-        None => return PathResult::new(vec![], 0.0, false),
-    };
-    
-    astar_pathfind(&start_grid, &goal_target, &cost_map, grid_config)
+    neighbors
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::structs::AgentState;
+/// Every grid cell the straight segment from `a` to `b` passes through, in order, paired
+/// with the length of the segment (in cell widths) that lies inside that cell. This is a
+/// supercover traversal: at a diagonal step it also emits the two neighboring cells the
+/// segment only grazes at their shared corner (each with zero length), matching the
+/// Bresenham/supercover convention that a line "touches" a cell it passes exactly between.
+fn supercover_cells(a: &GridNode, b: &GridNode) -> Vec<(GridNode, f64)> {
+    let dx = b.col as f64 - a.col as f64;
+    let dy = b.row as f64 - a.row as f64;
+    let total_length = sqrt(dx * dx + dy * dy);
 
-    #[test]
-    fn test_manhattan_distance() {
-        let a = GridNode::new(0, 0);
-        let b = GridNode::new(3, 4);
-        assert_eq!(manhattan_distance(&a, &b), 7.0);
-        
-        let c = GridNode::new(2, 2);
-        let d = GridNode::new(2, 2);
-        assert_eq!(manhattan_distance(&c, &d), 0.0);
+    if total_length < 1e-12 {
+        return vec![(a.clone(), 0.0)];
     }
 
-    #[test]
-    fn test_get_neighbors() {
-        let grid_config = GridConfig::new(5, 5, (-10.0, 10.0, -10.0, 10.0), 1.0, 1000.0);
-        
-        // Test center node
-        let center = GridNode::new(2, 2);
-        let neighbors = get_neighbors(&center, &grid_config);
-        assert_eq!(neighbors.len(), 4);
-        
-        // Test corner node
-        let corner = GridNode::new(0, 0);
-        let corner_neighbors = get_neighbors(&corner, &grid_config);
-        assert_eq!(corner_neighbors.len(), 2);
-        
-        // Test edge node
-        let edge = GridNode::new(0, 2);
-        let edge_neighbors = get_neighbors(&edge, &grid_config);
-        assert_eq!(edge_neighbors.len(), 3);
+    let step_col: i32 = if dx > 0.0 { 1 } else if dx < 0.0 { -1 } else { 0 };
+    let step_row: i32 = if dy > 0.0 { 1 } else if dy < 0.0 { -1 } else { 0 };
+
+    let t_delta_col = if dx.abs() > 1e-12 { 1.0 / dx.abs() } else { f64::INFINITY };
+    let t_delta_row = if dy.abs() > 1e-12 { 1.0 / dy.abs() } else { f64::INFINITY };
+
+    let mut t_max_col = if dx.abs() > 1e-12 { 0.5 / dx.abs() } else { f64::INFINITY };
+    let mut t_max_row = if dy.abs() > 1e-12 { 0.5 / dy.abs() } else { f64::INFINITY };
+
+    let mut col = a.col as i32;
+    let mut row = a.row as i32;
+    let end_col = b.col as i32;
+    let end_row = b.row as i32;
+
+    let mut cells = Vec::new();
+    let mut t_prev = 0.0;
+
+    loop {
+        if col == end_col && row == end_row {
+            cells.push((GridNode::new(row as usize, col as usize), (1.0 - t_prev) * total_length));
+            break;
+        }
+
+        let t_next = t_max_col.min(t_max_row);
+        cells.push((GridNode::new(row as usize, col as usize), (t_next - t_prev) * total_length));
+        t_prev = t_next;
+
+        if (t_max_col - t_max_row).abs() < 1e-9 {
+            cells.push((GridNode::new(row as usize, (col + step_col) as usize), 0.0));
+            cells.push((GridNode::new((row + step_row) as usize, col as usize), 0.0));
+            col += step_col;
+            row += step_row;
+            t_max_col += t_delta_col;
+            t_max_row += t_delta_row;
+        } else if t_max_col < t_max_row {
+            col += step_col;
+            t_max_col += t_delta_col;
+        } else {
+            row += step_row;
+            t_max_row += t_delta_row;
+        }
     }
 
-    #[test]
-    fn test_threat_map_generation() {
-        let grid_config = GridConfig::new(10, 10, (-5.0, 5.0, -5.0, 5.0), 1.0, 1000.0);
-        let sim_config = SimConfig::new(0.1, 1.0, 2.0, 1.0, 0.1);
-        
-        let defender = AgentState::new(Point::new(0.0, 0.0), Point::new(0.0, 0.0));
-        let intruder = AgentState::new(Point::new(3.0, 0.0), Point::new(0.0, 0.0));
-        let protected_zone = Circle::new(Point::new(-2.0, 0.0), 1.0);
-        
-        let world_state = WorldState::new(
-            vec![defender],
-            intruder,
-            protected_zone,
-        );
-        
-        let cost_map = generate_threat_map(&world_state, &grid_config, &sim_config);
-        
-        // Verify map dimensions
-        assert_eq!(cost_map.len(), 10);
-        assert_eq!(cost_map[0].len(), 10);
-        
-        // Check that base costs are applied
-        let mut has_base_cost = false;
-        let mut has_threat_cost = false;
-        
-        for row in &cost_map {
-            for &cost in row {
-                if (cost - grid_config.base_cost).abs() < 1e-10 {
-                    has_base_cost = true;
-                }
-                if cost > grid_config.base_cost + 100.0 {
-                    has_threat_cost = true;
-                }
-            }
+    cells
+}
+
+/// Whether the straight segment from `a` to `b` is unobstructed, per a supercover traversal
+/// of every cell it touches. Returns `None` if any traversed cell is out of bounds or has
+/// `f64::INFINITY` cost (blocked); otherwise `Some` of the segment's cost, the sum of each
+/// traversed cell's cost weighted by the length of the segment inside it - so a
+/// Theta* shortcut through a high-threat area still costs more than going around it.
+fn line_of_sight(
+    a: &GridNode,
+    b: &GridNode,
+    cost_map: &Vec<Vec<f64>>,
+    grid_config: &GridConfig,
+) -> Option<f64> {
+    let mut total_cost = 0.0;
+    for (cell, length) in supercover_cells(a, b) {
+        if !is_valid_grid_position(cell.row, cell.col, grid_config) {
+            return None;
         }
-        
-        assert!(has_base_cost);
-        assert!(has_threat_cost);
+        let cost = cost_map[cell.row][cell.col];
+        if cost == f64::INFINITY {
+            return None;
+        }
+        total_cost += cost * length;
     }
+    Some(total_cost)
+}
 
-    #[test]
-    fn test_astar_simple_path() {
-        let grid_config = GridConfig::new(5, 5, (-2.5, 2.5, -2.5, 2.5), 1.0, 1000.0);
-        let cost_map = vec![vec![1.0; 5]; 5]; // Uniform cost
-        
-        let start = GridNode::new(0, 0);
-        let goal = GridNode::new(4, 4);
-        
-        let result = astar_pathfind(&start, &goal, &cost_map, &grid_config);
-        
-        assert!(result.found);
-        assert_eq!(result.path.len(), 9); // 8 steps + start position
-        assert_eq!(result.path[0], start);
-        assert_eq!(result.path[result.path.len() - 1], goal);
+/// Any-angle pathfinding (Theta*) over the same `cost_map` as `astar_pathfind`, searching an
+/// 8-connected grid with Euclidean step costs and heuristic. When relaxing a neighbor `s`
+/// from the current node `c`: if `line_of_sight(parent(c), s)` is clear, `s` is parented
+/// directly to `parent(c)` with `g(s) = g(parent(c)) + line_of_sight_cost` ("straightening"
+/// the path through the grandparent); otherwise it falls back to the ordinary A* edge,
+/// parented to `c` with `g(s) = g(c) + line_of_sight_cost(c, s)`, taken only when that single
+/// step is itself clear. This produces
+/// smooth diagonal routes instead of A*'s jagged axis-aligned ones, while still respecting
+/// the threat map on both the straightened and fallback edges.
+pub fn theta_star_pathfind(
+    start: &GridNode,
+    goal: &GridNode,
+    cost_map: &Vec<Vec<f64>>,
+    grid_config: &GridConfig,
+) -> PathResult {
+    if !is_valid_grid_position(start.row, start.col, grid_config) ||
+       !is_valid_grid_position(goal.row, goal.col, grid_config) {
+        return PathResult::new(vec![], 0.0, false);
     }
 
-    #[test]
-    fn test_astar_blocked_path() {
-        let grid_config = GridConfig::new(3, 3, (-1.5, 1.5, -1.5, 1.5), 1.0, 1000.0);
-        let mut cost_map = vec![vec![1.0; 3]; 3];
-        
-        // Create a completely blocked scenario - block middle column entirely
-        for row in 0..3 {
+    if start == goal {
+        return PathResult::new(vec![start.clone()], 0.0, true);
+    }
+
+    let mut open_set = BinaryHeap::new();
+    let mut closed_set = HashSet::new();
+    // Sparse parent pointers: a node absent here (only `start`) is its own parent, per the
+    // usual Theta* convention.
+    let mut came_from: HashMap<GridNode, GridNode> = HashMap::new();
+    let mut g_scores = HashMap::new();
+
+    let start_node = AStarNode {
+        position: start.clone(),
+        g_cost: 0.0,
+        h_cost: euclidean_distance(start, goal),
+        f_cost: euclidean_distance(start, goal),
+        parent: None,
+    };
+
+    g_scores.insert(start.clone(), 0.0);
+    open_set.push(start_node);
+
+    while let Some(current) = open_set.pop() {
+        if current.position == *goal {
+            let path = reconstruct_path(goal, &came_from);
+            return PathResult::new(path, current.g_cost, true);
+        }
+
+        if closed_set.contains(&current.position) {
+            continue;
+        }
+        closed_set.insert(current.position.clone());
+
+        let parent_of_current = came_from.get(&current.position).cloned().unwrap_or_else(|| current.position.clone());
+
+        for neighbor in get_neighbors_8(&current.position, grid_config) {
+            if closed_set.contains(&neighbor) {
+                continue;
+            }
+
+            let neighbor_cost = cost_map[neighbor.row][neighbor.col];
+            if neighbor_cost == f64::INFINITY {
+                continue;
+            }
+
+            // Prefer straightening through the grandparent when it has line of sight; otherwise
+            // fall back to the ordinary one-step edge, but only if it too passes `line_of_sight`
+            // - an 8-connected diagonal step is itself a supercover segment, and must be
+            // rejected the same way a longer shortcut would be when it cuts between two
+            // blocked flanking cells at their shared corner.
+            let (tentative_g, new_parent) = match line_of_sight(&parent_of_current, &neighbor, cost_map, grid_config) {
+                Some(los_cost) => (g_scores[&parent_of_current] + los_cost, parent_of_current.clone()),
+                None => match line_of_sight(&current.position, &neighbor, cost_map, grid_config) {
+                    Some(step_cost) => (current.g_cost + step_cost, current.position.clone()),
+                    None => continue,
+                },
+            };
+
+            let is_better = match g_scores.get(&neighbor) {
+                Some(&existing_g) => tentative_g < existing_g,
+                None => true,
+            };
+
+            if is_better {
+                came_from.insert(neighbor.clone(), new_parent.clone());
+                g_scores.insert(neighbor.clone(), tentative_g);
+
+                let h_cost = euclidean_distance(&neighbor, goal);
+                open_set.push(AStarNode {
+                    position: neighbor.clone(),
+                    g_cost: tentative_g,
+                    h_cost,
+                    f_cost: tentative_g + h_cost,
+                    parent: Some(new_parent),
+                });
+            }
+        }
+    }
+
+    PathResult::new(vec![], 0.0, false)
+}
+
+/// A chunk coordinate in a `HierarchicalPathCache`'s fixed-size partition of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ChunkId {
+    row: usize,
+    col: usize,
+}
+
+/// An abstract-graph node placed at the midpoint of an "entrance" - a maximal contiguous
+/// span of cells passable from both sides of a chunk boundary.
+#[derive(Debug, Clone)]
+struct BorderNode {
+    position: GridNode,
+    chunk: ChunkId,
+}
+
+/// A precomputed edge in the abstract graph between two border nodes (or, for a query's
+/// temporary nodes, between a start/goal and a border node). Carries the concrete grid path
+/// alongside the cost so a query can stitch cached sub-paths straight into a `PathResult`
+/// instead of re-running A* over the fine grid.
+#[derive(Debug, Clone)]
+struct AbstractEdge {
+    to: usize,
+    cost: f64,
+    path: Vec<GridNode>,
+}
+
+/// Search node for A* over the small abstract graph, ordered by `f_cost` like `AStarNode`.
+#[derive(Debug, Clone, PartialEq)]
+struct AbstractSearchNode {
+    id: usize,
+    f_cost: f64,
+}
+
+impl Eq for AbstractSearchNode {}
+
+impl Ord for AbstractSearchNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.f_cost.partial_cmp(&self.f_cost).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for AbstractSearchNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A two-level (HPA*-style) pathfinder that precomputes an abstract graph over `GridConfig`'s
+/// cells so a query searches a handful of chunk-boundary nodes instead of re-running A* over
+/// the full fine grid every simulation step.
+///
+/// The grid is partitioned into `chunk_size`x`chunk_size` chunks. Along each boundary between
+/// adjacent chunks, maximal contiguous spans of passable cells ("entrances") each get a pair
+/// of border nodes - one per side - joined by a single-step inter-chunk edge. Within each
+/// chunk, every pair of its border nodes is joined by an intra-chunk edge whose cost and
+/// concrete sub-path come from a local `astar_pathfind` run (the within-chunk primitive)
+/// between them. A query adds `start`/`goal` as temporary nodes wired to their chunk's border
+/// nodes the same way, runs A* over the resulting abstract graph, then stitches the cached
+/// sub-paths into one `PathResult`.
+///
+/// Entrances themselves are fixed by the grid's static (`f64::INFINITY`) obstacles, not by
+/// the threat map layered on top, so they never need to be re-placed. Their single-step
+/// inter-chunk edge costs do move with the threat map, though (an entrance cell is just as
+/// capable of sitting under a defender's Apollonian circle as any other cell), so
+/// `mark_dirty` refreshes those alongside the intra-chunk edges `rebuild_chunk`/
+/// `rebuild_chunk_edges` recompute.
+#[derive(Clone)]
+pub struct HierarchicalPathCache {
+    grid_config: GridConfig,
+    cost_map: Vec<Vec<f64>>,
+    chunk_size: usize,
+    nodes: Vec<BorderNode>,
+    edges: Vec<Vec<AbstractEdge>>,
+    chunk_nodes: HashMap<ChunkId, Vec<usize>>,
+}
+
+impl HierarchicalPathCache {
+    pub fn new(cost_map: Vec<Vec<f64>>, grid_config: &GridConfig, chunk_size: usize) -> Self {
+        let mut cache = HierarchicalPathCache {
+            grid_config: grid_config.clone(),
+            cost_map,
+            chunk_size,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            chunk_nodes: HashMap::new(),
+        };
+        cache.build_entrances();
+        let chunk_ids: Vec<ChunkId> = cache.chunk_nodes.keys().cloned().collect();
+        for chunk_id in chunk_ids {
+            cache.rebuild_chunk_edges(chunk_id);
+        }
+        cache
+    }
+
+    fn num_chunk_rows(&self) -> usize {
+        (self.grid_config.height + self.chunk_size - 1) / self.chunk_size
+    }
+
+    fn num_chunk_cols(&self) -> usize {
+        (self.grid_config.width + self.chunk_size - 1) / self.chunk_size
+    }
+
+    fn chunk_of(&self, node: &GridNode) -> ChunkId {
+        ChunkId { row: node.row / self.chunk_size, col: node.col / self.chunk_size }
+    }
+
+    fn add_node(&mut self, position: GridNode, chunk: ChunkId) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(BorderNode { position, chunk });
+        self.edges.push(Vec::new());
+        self.chunk_nodes.entry(chunk).or_insert_with(Vec::new).push(id);
+        id
+    }
+
+    /// Place paired border nodes and inter-chunk edges at every entrance along every chunk
+    /// boundary. Run once at construction.
+    fn build_entrances(&mut self) {
+        // Boundaries between horizontally-adjacent chunks (a shared vertical edge).
+        for chunk_row in 0..self.num_chunk_rows() {
+            for chunk_col in 0..self.num_chunk_cols().saturating_sub(1) {
+                let left_col = ((chunk_col + 1) * self.chunk_size).min(self.grid_config.width) - 1;
+                let right_col = left_col + 1;
+                if right_col >= self.grid_config.width {
+                    continue;
+                }
+
+                let row_start = chunk_row * self.chunk_size;
+                let row_end = (row_start + self.chunk_size).min(self.grid_config.height);
+
+                self.build_boundary_entrances(
+                    row_start,
+                    row_end,
+                    |index| GridNode::new(index, left_col),
+                    |index| GridNode::new(index, right_col),
+                    ChunkId { row: chunk_row, col: chunk_col },
+                    ChunkId { row: chunk_row, col: chunk_col + 1 },
+                );
+            }
+        }
+
+        // Boundaries between vertically-adjacent chunks (a shared horizontal edge).
+        for chunk_col in 0..self.num_chunk_cols() {
+            for chunk_row in 0..self.num_chunk_rows().saturating_sub(1) {
+                let top_row = ((chunk_row + 1) * self.chunk_size).min(self.grid_config.height) - 1;
+                let bottom_row = top_row + 1;
+                if bottom_row >= self.grid_config.height {
+                    continue;
+                }
+
+                let col_start = chunk_col * self.chunk_size;
+                let col_end = (col_start + self.chunk_size).min(self.grid_config.width);
+
+                self.build_boundary_entrances(
+                    col_start,
+                    col_end,
+                    |index| GridNode::new(top_row, index),
+                    |index| GridNode::new(bottom_row, index),
+                    ChunkId { row: chunk_row, col: chunk_col },
+                    ChunkId { row: chunk_row + 1, col: chunk_col },
+                );
+            }
+        }
+    }
+
+    /// Scan `index_start..index_end` along one boundary (`near_at`/`far_at` map an index to
+    /// the cell on each side), placing a paired border node at the midpoint of every maximal
+    /// span where both sides are passable, with a single-step inter-chunk edge between them.
+    fn build_boundary_entrances(
+        &mut self,
+        index_start: usize,
+        index_end: usize,
+        near_at: impl Fn(usize) -> GridNode,
+        far_at: impl Fn(usize) -> GridNode,
+        near_chunk: ChunkId,
+        far_chunk: ChunkId,
+    ) {
+        let mut span_start: Option<usize> = None;
+
+        // Iterate one index past the end so an open span still gets flushed.
+        for index in index_start..=index_end {
+            let passable = index < index_end && {
+                let near = near_at(index);
+                let far = far_at(index);
+                self.cost_map[near.row][near.col] != f64::INFINITY
+                    && self.cost_map[far.row][far.col] != f64::INFINITY
+            };
+
+            if passable {
+                if span_start.is_none() {
+                    span_start = Some(index);
+                }
+            } else if let Some(start) = span_start.take() {
+                let mid = (start + index - 1) / 2;
+                let near_node = near_at(mid);
+                let far_node = far_at(mid);
+
+                let near_id = self.add_node(near_node.clone(), near_chunk);
+                let far_id = self.add_node(far_node.clone(), far_chunk);
+
+                let enter_far_cost = self.cost_map[far_node.row][far_node.col];
+                let enter_near_cost = self.cost_map[near_node.row][near_node.col];
+                self.edges[near_id].push(AbstractEdge {
+                    to: far_id,
+                    cost: enter_far_cost,
+                    path: vec![near_node.clone(), far_node.clone()],
+                });
+                self.edges[far_id].push(AbstractEdge {
+                    to: near_id,
+                    cost: enter_near_cost,
+                    path: vec![far_node, near_node],
+                });
+            }
+        }
+    }
+
+    /// Recompute every intra-chunk edge among `chunk_id`'s border nodes against the current
+    /// `cost_map`, using `astar_pathfind` as the within-chunk primitive. Leaves inter-chunk
+    /// edges (to nodes in a different chunk) untouched, since entrances never move.
+    pub fn rebuild_chunk(&mut self, chunk_id: ChunkId) {
+        self.rebuild_chunk_edges(chunk_id);
+    }
+
+    fn rebuild_chunk_edges(&mut self, chunk_id: ChunkId) {
+        let node_ids = match self.chunk_nodes.get(&chunk_id) {
+            Some(ids) => ids.clone(),
+            None => return,
+        };
+
+        let node_chunks: Vec<ChunkId> = self.nodes.iter().map(|n| n.chunk).collect();
+        for &id in &node_ids {
+            self.edges[id].retain(|edge| node_chunks[edge.to] != chunk_id);
+        }
+
+        for &from in &node_ids {
+            for &to in &node_ids {
+                if from == to {
+                    continue;
+                }
+                let result = astar_pathfind(
+                    &self.nodes[from].position,
+                    &self.nodes[to].position,
+                    &self.cost_map,
+                    &self.grid_config,
+                );
+                if result.found {
+                    self.edges[from].push(AbstractEdge { to, cost: result.cost, path: result.path });
+                }
+            }
+        }
+    }
+
+    /// Update the cost map, refresh every entrance edge's cost against it, and rebuild every
+    /// chunk a circle (typically a defender's moved Apollonian circle) overlaps, so only the
+    /// affected chunks' intra-chunk edges are recomputed rather than rebuilding the whole
+    /// abstract graph.
+    pub fn mark_dirty(&mut self, cost_map: Vec<Vec<f64>>, circle: &Circle) {
+        self.cost_map = cost_map;
+        self.refresh_entrance_costs();
+
+        for chunk_id in self.chunks_overlapping(circle) {
+            self.rebuild_chunk_edges(chunk_id);
+        }
+    }
+
+    /// Like `mark_dirty`, but for a whole tick's worth of circles at once: the cost map is
+    /// replaced and every entrance cost refreshed a single time, then the union of every
+    /// circle's overlapping chunks is rebuilt, rather than repeating both per circle the way
+    /// calling `mark_dirty` once per defender would.
+    pub fn mark_all_dirty(&mut self, cost_map: Vec<Vec<f64>>, circles: &[Circle]) {
+        self.cost_map = cost_map;
+        self.refresh_entrance_costs();
+
+        let mut dirty_chunks = HashSet::new();
+        for circle in circles {
+            dirty_chunks.extend(self.chunks_overlapping(circle));
+        }
+
+        for chunk_id in dirty_chunks {
+            self.rebuild_chunk_edges(chunk_id);
+        }
+    }
+
+    /// Every chunk `circle`'s bounding box overlaps, or none for an infinite-radius circle
+    /// (the equal-speed-defender convention used throughout this module), which never marks
+    /// a finite region dirty.
+    fn chunks_overlapping(&self, circle: &Circle) -> HashSet<ChunkId> {
+        let mut chunks = HashSet::new();
+        if circle.radius == f64::INFINITY {
+            return chunks;
+        }
+
+        let (min_x, max_x, min_y, max_y) = self.grid_config.world_bounds;
+        let clamp = |p: Point| Point::new(p.x.max(min_x).min(max_x), p.y.max(min_y).min(max_y));
+
+        let min_corner = clamp(Point::new(circle.center.x - circle.radius, circle.center.y - circle.radius));
+        let max_corner = clamp(Point::new(circle.center.x + circle.radius, circle.center.y + circle.radius));
+
+        let (Some(min_grid), Some(max_grid)) = (
+            to_grid_coords(&min_corner, &self.grid_config),
+            to_grid_coords(&max_corner, &self.grid_config),
+        ) else {
+            return chunks;
+        };
+
+        let (row_lo, row_hi) = (min_grid.row.min(max_grid.row), min_grid.row.max(max_grid.row));
+        let (col_lo, col_hi) = (min_grid.col.min(max_grid.col), min_grid.col.max(max_grid.col));
+
+        for row in row_lo..=row_hi {
+            for col in col_lo..=col_hi {
+                chunks.insert(ChunkId { row: row / self.chunk_size, col: col / self.chunk_size });
+            }
+        }
+        chunks
+    }
+
+    /// Recompute every inter-chunk "entrance" edge's cost from the current `cost_map`.
+    /// `rebuild_chunk_edges` deliberately leaves these untouched (entrances themselves never
+    /// move), so without this a chokepoint's cached cost goes stale the moment a defender's
+    /// threat moves onto or off an entrance cell.
+    fn refresh_entrance_costs(&mut self) {
+        let node_chunks: Vec<ChunkId> = self.nodes.iter().map(|n| n.chunk).collect();
+        for id in 0..self.nodes.len() {
+            for edge in self.edges[id].iter_mut() {
+                if node_chunks[edge.to] != node_chunks[id] {
+                    let to_pos = &self.nodes[edge.to].position;
+                    edge.cost = self.cost_map[to_pos.row][to_pos.col];
+                }
+            }
+        }
+    }
+
+    /// Find the best path from `start` to `goal` over the abstract graph: wire both in as
+    /// temporary nodes connected to their chunk's border nodes (and, when they share a chunk,
+    /// to each other) via local
+    /// `astar_pathfind` legs, run A* over the small resulting graph, then stitch the
+    /// reconstructed node sequence's cached/temporary sub-paths into one `PathResult`.
+    pub fn query(&self, start: &GridNode, goal: &GridNode) -> PathResult {
+        if !is_valid_grid_position(start.row, start.col, &self.grid_config)
+            || !is_valid_grid_position(goal.row, goal.col, &self.grid_config)
+        {
+            return PathResult::new(vec![], 0.0, false);
+        }
+        if start == goal {
+            return PathResult::new(vec![start.clone()], 0.0, true);
+        }
+
+        let start_id = self.nodes.len();
+        let goal_id = start_id + 1;
+        let mut temp_edges: HashMap<usize, Vec<AbstractEdge>> = HashMap::new();
+
+        let connect = |from: &GridNode, to: &GridNode| astar_pathfind(from, to, &self.cost_map, &self.grid_config);
+
+        if self.chunk_of(start) == self.chunk_of(goal) {
+            let direct = connect(start, goal);
+            if direct.found {
+                temp_edges.entry(start_id).or_insert_with(Vec::new).push(AbstractEdge {
+                    to: goal_id,
+                    cost: direct.cost,
+                    path: direct.path,
+                });
+            }
+        }
+
+        if let Some(border_ids) = self.chunk_nodes.get(&self.chunk_of(start)) {
+            for &border_id in border_ids {
+                let result = connect(start, &self.nodes[border_id].position);
+                if result.found {
+                    temp_edges.entry(start_id).or_insert_with(Vec::new).push(AbstractEdge {
+                        to: border_id,
+                        cost: result.cost,
+                        path: result.path.clone(),
+                    });
+                    let mut back = result.path;
+                    back.reverse();
+                    temp_edges.entry(border_id).or_insert_with(Vec::new).push(AbstractEdge {
+                        to: start_id,
+                        cost: result.cost,
+                        path: back,
+                    });
+                }
+            }
+        }
+
+        if let Some(border_ids) = self.chunk_nodes.get(&self.chunk_of(goal)) {
+            for &border_id in border_ids {
+                let result = connect(&self.nodes[border_id].position, goal);
+                if result.found {
+                    temp_edges.entry(border_id).or_insert_with(Vec::new).push(AbstractEdge {
+                        to: goal_id,
+                        cost: result.cost,
+                        path: result.path.clone(),
+                    });
+                    let mut back = result.path;
+                    back.reverse();
+                    temp_edges.entry(goal_id).or_insert_with(Vec::new).push(AbstractEdge {
+                        to: border_id,
+                        cost: result.cost,
+                        path: back,
+                    });
+                }
+            }
+        }
+
+        let goal_position = goal.clone();
+        let heuristic = |id: usize| -> f64 {
+            if id == goal_id {
+                0.0
+            } else if id == start_id {
+                euclidean_distance(start, &goal_position)
+            } else {
+                euclidean_distance(&self.nodes[id].position, &goal_position)
+            }
+        };
+
+        let mut open_set = BinaryHeap::new();
+        let mut closed_set: HashSet<usize> = HashSet::new();
+        let mut came_from: HashMap<usize, usize> = HashMap::new();
+        let mut g_scores: HashMap<usize, f64> = HashMap::new();
+
+        g_scores.insert(start_id, 0.0);
+        open_set.push(AbstractSearchNode { id: start_id, f_cost: heuristic(start_id) });
+
+        let mut found = false;
+        while let Some(current) = open_set.pop() {
+            if current.id == goal_id {
+                found = true;
+                break;
+            }
+            if !closed_set.insert(current.id) {
+                continue;
+            }
+
+            let mut outgoing: Vec<&AbstractEdge> = if current.id < self.nodes.len() {
+                self.edges[current.id].iter().collect()
+            } else {
+                Vec::new()
+            };
+            if let Some(extra) = temp_edges.get(&current.id) {
+                outgoing.extend(extra.iter());
+            }
+
+            for edge in outgoing {
+                if closed_set.contains(&edge.to) {
+                    continue;
+                }
+                let tentative_g = g_scores[&current.id] + edge.cost;
+                let is_better = match g_scores.get(&edge.to) {
+                    Some(&existing) => tentative_g < existing,
+                    None => true,
+                };
+                if is_better {
+                    g_scores.insert(edge.to, tentative_g);
+                    came_from.insert(edge.to, current.id);
+                    open_set.push(AbstractSearchNode { id: edge.to, f_cost: tentative_g + heuristic(edge.to) });
+                }
+            }
+        }
+
+        if !found {
+            return PathResult::new(vec![], 0.0, false);
+        }
+
+        let mut node_sequence = vec![goal_id];
+        let mut current = goal_id;
+        while let Some(&parent) = came_from.get(&current) {
+            node_sequence.push(parent);
+            current = parent;
+        }
+        node_sequence.reverse();
+
+        let mut full_path: Vec<GridNode> = Vec::new();
+        for window in node_sequence.windows(2) {
+            let (from, to) = (window[0], window[1]);
+            let edge = self.find_edge(from, to, &temp_edges);
+            let mut segment = edge.path.clone();
+            if !full_path.is_empty() && !segment.is_empty() {
+                segment.remove(0); // Drop the duplicate junction cell shared with the previous leg.
+            }
+            full_path.extend(segment);
+        }
+
+        PathResult::new(full_path, g_scores[&goal_id], true)
+    }
+
+    fn find_edge<'a>(
+        &'a self,
+        from: usize,
+        to: usize,
+        temp_edges: &'a HashMap<usize, Vec<AbstractEdge>>,
+    ) -> &'a AbstractEdge {
+        let permanent = if from < self.nodes.len() {
+            self.edges[from].iter().find(|edge| edge.to == to)
+        } else {
+            None
+        };
+        permanent
+            .or_else(|| temp_edges.get(&from).and_then(|edges| edges.iter().find(|edge| edge.to == to)))
+            .expect("abstract graph edge must exist for a reconstructed path segment")
+    }
+}
+
+/// Chunk size `hierarchical_pathfind` partitions the grid into - small enough that a chunk's
+/// border-node count (and so the cost of `rebuild_chunk_edges`) stays cheap, large enough that
+/// the abstract graph stays small relative to the full grid.
+const HIERARCHICAL_CHUNK_SIZE: usize = 8;
+
+/// `PathfindingMode::Hierarchical`'s search primitive: build a `HierarchicalPathCache` over
+/// the current `cost_map` and immediately query it.
+///
+/// This still pays the cache's full construction cost on every call, so it only wins over
+/// `astar_pathfind` once the grid is large enough that a handful of small per-chunk searches
+/// beats one full-grid search. A caller re-planning every tick against a threat map that
+/// barely changes should build its own `HierarchicalPathCache` once and drive it directly with
+/// `mark_dirty`/`query`, rather than going through this per-call rebuild.
+fn hierarchical_pathfind(
+    start: &GridNode,
+    goal: &GridNode,
+    cost_map: &Vec<Vec<f64>>,
+    grid_config: &GridConfig,
+) -> PathResult {
+    let cache = HierarchicalPathCache::new(cost_map.clone(), grid_config, HIERARCHICAL_CHUNK_SIZE);
+    cache.query(start, goal)
+}
+
+/// Per-intruder hierarchical-pathfinding state carried between simulation steps: wraps a
+/// `HierarchicalPathCache` (built lazily on first use) so `calculate_intruder_next_position_with_cache`/
+/// `calculate_intruder_full_path_with_cache` can drive it with `mark_all_dirty`/`query` every
+/// tick instead of paying `HierarchicalPathCache::new`'s full construction cost on every call,
+/// the way `hierarchical_pathfind` does under the plain (non-cached) functions.
+#[pyclass]
+#[derive(Clone)]
+pub struct IntruderPathCache {
+    cache: Option<HierarchicalPathCache>,
+}
+
+#[pymethods]
+impl IntruderPathCache {
+    #[new]
+    pub fn new() -> Self {
+        IntruderPathCache { cache: None }
+    }
+}
+
+impl Default for IntruderPathCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `PathfindingMode::Hierarchical`'s search primitive for a caller holding a persistent
+/// `IntruderPathCache`: build the underlying `HierarchicalPathCache` on first use, then on
+/// every later call drive it with `mark_all_dirty` over the current tick's defender Apollonian
+/// circles instead of reconstructing it from scratch, the way `hierarchical_pathfind` does.
+fn hierarchical_pathfind_cached(
+    cache: &mut IntruderPathCache,
+    start: &GridNode,
+    goal: &GridNode,
+    cost_map: Vec<Vec<f64>>,
+    grid_config: &GridConfig,
+    world_state: &WorldState,
+    sim_config: &SimConfig,
+) -> PathResult {
+    let circles: Vec<Circle> = world_state
+        .defenders
+        .iter()
+        .map(|defender| {
+            calculate_apollonian_circle(&defender.position, &world_state.intruder.position, sim_config.speed_ratio())
+        })
+        .collect();
+
+    match &mut cache.cache {
+        Some(existing) => {
+            existing.mark_all_dirty(cost_map, &circles);
+            existing.query(start, goal)
+        }
+        None => {
+            let built = HierarchicalPathCache::new(cost_map, grid_config, HIERARCHICAL_CHUNK_SIZE);
+            let result = built.query(start, goal);
+            cache.cache = Some(built);
+            result
+        }
+    }
+}
+
+/// Calculate the next position for the intruder using A* pathfinding
+pub fn calculate_intruder_next_position(
+    world_state: &WorldState,
+    grid_config: &GridConfig,
+    sim_config: &SimConfig,
+) -> Option<Point> {
+    // Generate threat map
+    let cost_map = generate_threat_map(world_state, grid_config, sim_config);
+    
+    // Convert positions to grid coordinates
+    let start_grid = to_grid_coords(&world_state.intruder.position, grid_config)?;
+    
+    // Find the best goal target within the protected zone
+    let goal_target = find_best_goal_target(world_state, grid_config, &cost_map)?;
+    
+    // Run pathfinding to the best goal target, per config.pathfinding_mode
+    let path_result = match sim_config.pathfinding_mode {
+        PathfindingMode::AStar => astar_pathfind(&start_grid, &goal_target, &cost_map, grid_config),
+        PathfindingMode::ThetaStar => theta_star_pathfind(&start_grid, &goal_target, &cost_map, grid_config),
+        PathfindingMode::Hierarchical => hierarchical_pathfind(&start_grid, &goal_target, &cost_map, grid_config),
+    };
+
+    if !path_result.found || path_result.path.len() < 2 {
+        return None; // No path found or already at goal
+    }
+    
+    // Return the next step in the path (convert back to world coordinates)
+    let next_grid_pos = &path_result.path[1];
+    Some(to_world_coords(next_grid_pos, grid_config))
+}
+
+/// Like `calculate_intruder_next_position`, but drives `PathfindingMode::Hierarchical` with
+/// `cache`'s persistent `HierarchicalPathCache` instead of rebuilding one from scratch every
+/// call. The caller holds `cache` across simulation steps - the same pattern
+/// `get_defender_velocity_commands_with_states` uses for `DefenderFsmState` - so repeated calls
+/// actually get the cheaper incremental replanning `mark_all_dirty`/`query` offer. Other modes
+/// ignore `cache` entirely.
+pub fn calculate_intruder_next_position_with_cache(
+    world_state: &WorldState,
+    grid_config: &GridConfig,
+    sim_config: &SimConfig,
+    cache: &mut IntruderPathCache,
+) -> Option<Point> {
+    let cost_map = generate_threat_map(world_state, grid_config, sim_config);
+    let start_grid = to_grid_coords(&world_state.intruder.position, grid_config)?;
+    let goal_target = find_best_goal_target(world_state, grid_config, &cost_map)?;
+
+    let path_result = match sim_config.pathfinding_mode {
+        PathfindingMode::AStar => astar_pathfind(&start_grid, &goal_target, &cost_map, grid_config),
+        PathfindingMode::ThetaStar => theta_star_pathfind(&start_grid, &goal_target, &cost_map, grid_config),
+        PathfindingMode::Hierarchical => hierarchical_pathfind_cached(
+            cache, &start_grid, &goal_target, cost_map, grid_config, world_state, sim_config,
+        ),
+    };
+
+    if !path_result.found || path_result.path.len() < 2 {
+        return None;
+    }
+
+    let next_grid_pos = &path_result.path[1];
+    Some(to_world_coords(next_grid_pos, grid_config))
+}
+
+/// Find the best goal target within the protected zone circle
+fn find_best_goal_target(
+    world_state: &WorldState,
+    grid_config: &GridConfig,
+    cost_map: &Vec<Vec<f64>>,
+) -> Option<GridNode> {
+    let protected_zone = &world_state.protected_zone;
+    
+    // If intruder is already in goal zone, return current position
+    if protected_zone.contains_point(&world_state.intruder.position) {
+        return to_grid_coords(&world_state.intruder.position, grid_config);
+    }
+    
+    // Find all grid cells within the protected zone
+    let mut goal_candidates = Vec::new();
+    
+    // Calculate grid bounds around the protected zone
+    let zone_center_grid = to_grid_coords(&protected_zone.center, grid_config)?;
+    let (min_x, max_x, min_y, max_y) = grid_config.world_bounds;
+    let cell_size = ((max_x - min_x) / grid_config.width as f64)
+        .max((max_y - min_y) / grid_config.height as f64);
+    let grid_radius = (protected_zone.radius / cell_size).ceil() as usize + 1;
+    
+    let start_row = zone_center_grid.row.saturating_sub(grid_radius);
+    let end_row = (zone_center_grid.row + grid_radius + 1).min(grid_config.height);
+    let start_col = zone_center_grid.col.saturating_sub(grid_radius);
+    let end_col = (zone_center_grid.col + grid_radius + 1).min(grid_config.width);
+    
+    for row in start_row..end_row {
+        for col in start_col..end_col {
+            let node = GridNode::new(row, col);
+            let world_pos = to_world_coords(&node, grid_config);
+            
+            // Check if this grid cell is within the protected zone
+            if protected_zone.contains_point(&world_pos) {
+                goal_candidates.push((node, cost_map[row][col]));
+            }
+        }
+    }
+    
+    if goal_candidates.is_empty() {
+        // Fallback to center if no candidates found
+        return to_grid_coords(&protected_zone.center, grid_config);
+    }
+    
+    // Select the goal candidate with the lowest cost (safest path)
+    goal_candidates.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+    Some(goal_candidates[0].0.clone())
+}
+
+/// Get the full path for visualization purposes
+pub fn calculate_intruder_full_path(
+    world_state: &WorldState,
+    grid_config: &GridConfig,
+    sim_config: &SimConfig,
+) -> PathResult {
+    let cost_map = generate_threat_map(world_state, grid_config, sim_config);
+    
+    let start_grid = match to_grid_coords(&world_state.intruder.position, grid_config) {
+        Some(pos) => pos,
+        None => return PathResult::new(vec![], 0.0, false),
+    };
+    
+    let goal_target = match find_best_goal_target(world_state, grid_config, &cost_map) {
+        Some(target) => target,
+        None => return PathResult::new(vec![], 0.0, false),
+    };
+
+    match sim_config.pathfinding_mode {
+        PathfindingMode::AStar => astar_pathfind(&start_grid, &goal_target, &cost_map, grid_config),
+        PathfindingMode::ThetaStar => theta_star_pathfind(&start_grid, &goal_target, &cost_map, grid_config),
+        PathfindingMode::Hierarchical => hierarchical_pathfind(&start_grid, &goal_target, &cost_map, grid_config),
+    }
+}
+
+/// Like `calculate_intruder_full_path`, but drives `PathfindingMode::Hierarchical` with
+/// `cache`'s persistent `HierarchicalPathCache` instead of rebuilding one from scratch every
+/// call - see `calculate_intruder_next_position_with_cache`.
+pub fn calculate_intruder_full_path_with_cache(
+    world_state: &WorldState,
+    grid_config: &GridConfig,
+    sim_config: &SimConfig,
+    cache: &mut IntruderPathCache,
+) -> PathResult {
+    let cost_map = generate_threat_map(world_state, grid_config, sim_config);
+
+    let start_grid = match to_grid_coords(&world_state.intruder.position, grid_config) {
+        Some(pos) => pos,
+        None => return PathResult::new(vec![], 0.0, false),
+    };
+
+    let goal_target = match find_best_goal_target(world_state, grid_config, &cost_map) {
+        Some(target) => target,
+        None => return PathResult::new(vec![], 0.0, false),
+    };
+
+    match sim_config.pathfinding_mode {
+        PathfindingMode::AStar => astar_pathfind(&start_grid, &goal_target, &cost_map, grid_config),
+        PathfindingMode::ThetaStar => theta_star_pathfind(&start_grid, &goal_target, &cost_map, grid_config),
+        PathfindingMode::Hierarchical => hierarchical_pathfind_cached(
+            cache, &start_grid, &goal_target, cost_map, grid_config, world_state, sim_config,
+        ),
+    }
+}
+
+/// A node of the continuous-space visibility graph built by `visibility_graph_pathfind`: a
+/// world point that is either an endpoint (`on_circle: None`) or a tangent point lying on one
+/// obstacle circle's boundary.
+#[derive(Debug, Clone)]
+struct VisibilityNode {
+    position: Point,
+    on_circle: Option<usize>,
+}
+
+/// Search node for Dijkstra over the visibility graph, ordered by accumulated cost like
+/// `AStarNode`.
+#[derive(Debug, Clone, PartialEq)]
+struct VisibilitySearchNode {
+    id: usize,
+    cost: f64,
+}
+
+impl Eq for VisibilitySearchNode {}
+
+impl Ord for VisibilitySearchNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.partial_cmp(&self.cost).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for VisibilitySearchNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn normalize_angle(angle: f64) -> f64 {
+    let mut a = angle % (2.0 * PI);
+    if a < 0.0 {
+        a += 2.0 * PI;
+    }
+    a
+}
+
+/// External tangent lines between two circles: pairs `(t1, t2)` with `t1` on `c1`'s boundary
+/// and `t2` on `c2`'s, where both circles stay on the same side of the tangent line. Up to two
+/// solutions; none when one circle contains the other (`|r1 - r2| > d`).
+fn external_tangent_points(c1: &Circle, c2: &Circle) -> Vec<(Point, Point)> {
+    let d = c1.center.distance_to(&c2.center);
+    if d < 1e-9 {
+        return vec![];
+    }
+
+    let cos_phi = (c1.radius - c2.radius) / d;
+    if cos_phi.abs() > 1.0 {
+        return vec![];
+    }
+
+    let phi = acos(cos_phi);
+    let base_angle = c1.center.angle_to(&c2.center);
+
+    [base_angle + phi, base_angle - phi]
+        .into_iter()
+        .map(|theta| {
+            let normal = Point::new(cos(theta), sin(theta));
+            (
+                Point::new(c1.center.x + c1.radius * normal.x, c1.center.y + c1.radius * normal.y),
+                Point::new(c2.center.x + c2.radius * normal.x, c2.center.y + c2.radius * normal.y),
+            )
+        })
+        .collect()
+}
+
+/// Internal tangent lines between two circles: like `external_tangent_points`, but the two
+/// circles fall on opposite sides of the tangent line. Only defined when the circles are
+/// disjoint (`d > r1 + r2`).
+fn internal_tangent_points(c1: &Circle, c2: &Circle) -> Vec<(Point, Point)> {
+    let d = c1.center.distance_to(&c2.center);
+    if d < 1e-9 {
+        return vec![];
+    }
+
+    let cos_phi = (c1.radius + c2.radius) / d;
+    if cos_phi.abs() > 1.0 {
+        return vec![];
+    }
+
+    let phi = acos(cos_phi);
+    let base_angle = c1.center.angle_to(&c2.center);
+
+    [base_angle + phi, base_angle - phi]
+        .into_iter()
+        .map(|theta| {
+            let normal = Point::new(cos(theta), sin(theta));
+            (
+                Point::new(c1.center.x + c1.radius * normal.x, c1.center.y + c1.radius * normal.y),
+                Point::new(c2.center.x - c2.radius * normal.x, c2.center.y - c2.radius * normal.y),
+            )
+        })
+        .collect()
+}
+
+/// Distance from `point` to the nearest point ON the segment `p1`-`p2` (not the infinite
+/// line), i.e. the perpendicular distance restricted to the segment's own parameter range.
+fn point_to_segment_distance(point: &Point, p1: &Point, p2: &Point) -> f64 {
+    let dx = p2.x - p1.x;
+    let dy = p2.y - p1.y;
+    let len_sq = dx * dx + dy * dy;
+    if len_sq < 1e-12 {
+        return point.distance_to(p1);
+    }
+
+    let t = (((point.x - p1.x) * dx + (point.y - p1.y) * dy) / len_sq).max(0.0).min(1.0);
+    let closest = Point::new(p1.x + t * dx, p1.y + t * dy);
+    point.distance_to(&closest)
+}
+
+/// A straight edge between `p1` and `p2` is admissible only if it doesn't cross the interior
+/// of any obstacle other than the (at most two) circles its own endpoints lie on.
+fn segment_clear_of_obstacles(p1: &Point, p2: &Point, obstacles: &[Circle], skip: &[usize]) -> bool {
+    obstacles.iter().enumerate().all(|(i, circle)| {
+        skip.contains(&i)
+            || circle.radius == f64::INFINITY
+            || point_to_segment_distance(&circle.center, p1, p2) >= circle.radius - 1e-9
+    })
+}
+
+/// Plan a provably shortest path from `start` to `goal` in continuous space, treating each of
+/// `obstacles` (e.g. defenders' Apollonian circles) as a hard circular obstacle - an
+/// alternative to grid-discretized search (`astar_pathfind`/`theta_star_pathfind`) that avoids
+/// grid-resolution artifacts entirely.
+///
+/// Builds a visibility graph from `start`, `goal`, and the tangent points on each obstacle
+/// reachable from those endpoints and from each other obstacle (both external tangents, and
+/// internal tangents when a pair of obstacles is disjoint). A straight edge between two graph
+/// nodes is kept only if it doesn't cross the interior of any *other* obstacle; an arc edge
+/// connects tangent points on the same circle only along the stretches of that circle's
+/// boundary not buried inside another obstacle (reusing `calculate_boundary_coverage`'s
+/// gaps), at cost `r * delta_theta`. Dijkstra (a min-heap ordered like `AStarNode`, with no
+/// heuristic - tangent-point geometry doesn't offer a cheap admissible one) then finds the
+/// shortest walk from `start` to `goal` over this graph.
+///
+/// An obstacle that already contains `start` or `goal` contributes no tangent points from
+/// that endpoint, but it still blocks every straight edge crossing its interior, so such an
+/// endpoint is unreachable and the result is not found; the caller is responsible for
+/// choosing endpoints outside every obstacle.
+pub fn visibility_graph_pathfind(start: &Point, goal: &Point, obstacles: &[Circle]) -> VisibilityPathResult {
+    const START: usize = 0;
+    const GOAL: usize = 1;
+
+    let mut nodes = vec![
+        VisibilityNode { position: start.clone(), on_circle: None },
+        VisibilityNode { position: goal.clone(), on_circle: None },
+    ];
+
+    // Edges are restricted to the specific analytically-constructed tangent segments (plus
+    // the direct start-goal line) - never an arbitrary pair of graph nodes. A true tangent
+    // segment only touches its own circle(s) at the tangent point(s), so admissibility only
+    // needs checking against every *other* obstacle; a chord between two unrelated points on
+    // the same circle, by contrast, generally cuts through that circle's interior, which is
+    // why such pairs are never proposed as edges here at all.
+    let mut adjacency: Vec<Vec<(usize, f64)>> = vec![Vec::new(); nodes.len()];
+
+    let add_edge = |nodes: &mut Vec<VisibilityNode>, adjacency: &mut Vec<Vec<(usize, f64)>>, a: usize, b: usize, skip: &[usize]| {
+        if segment_clear_of_obstacles(&nodes[a].position, &nodes[b].position, obstacles, skip) {
+            let cost = nodes[a].position.distance_to(&nodes[b].position);
+            adjacency[a].push((b, cost));
+            adjacency[b].push((a, cost));
+        }
+    };
+
+    add_edge(&mut nodes, &mut adjacency, START, GOAL, &[]);
+
+    for (i, circle) in obstacles.iter().enumerate() {
+        if circle.radius == f64::INFINITY || circle.contains_point(start) || circle.contains_point(goal) {
+            continue;
+        }
+        if let Some((t1, t2)) = circle.tangent_lines_from(start) {
+            let id1 = nodes.len();
+            nodes.push(VisibilityNode { position: t1, on_circle: Some(i) });
+            adjacency.push(Vec::new());
+            let id2 = nodes.len();
+            nodes.push(VisibilityNode { position: t2, on_circle: Some(i) });
+            adjacency.push(Vec::new());
+            add_edge(&mut nodes, &mut adjacency, START, id1, &[i]);
+            add_edge(&mut nodes, &mut adjacency, START, id2, &[i]);
+        }
+        if let Some((t1, t2)) = circle.tangent_lines_from(goal) {
+            let id1 = nodes.len();
+            nodes.push(VisibilityNode { position: t1, on_circle: Some(i) });
+            adjacency.push(Vec::new());
+            let id2 = nodes.len();
+            nodes.push(VisibilityNode { position: t2, on_circle: Some(i) });
+            adjacency.push(Vec::new());
+            add_edge(&mut nodes, &mut adjacency, GOAL, id1, &[i]);
+            add_edge(&mut nodes, &mut adjacency, GOAL, id2, &[i]);
+        }
+    }
+
+    for i in 0..obstacles.len() {
+        if obstacles[i].radius == f64::INFINITY {
+            continue;
+        }
+        for j in (i + 1)..obstacles.len() {
+            if obstacles[j].radius == f64::INFINITY {
+                continue;
+            }
+
+            for (t1, t2) in external_tangent_points(&obstacles[i], &obstacles[j]) {
+                let id1 = nodes.len();
+                nodes.push(VisibilityNode { position: t1, on_circle: Some(i) });
+                adjacency.push(Vec::new());
+                let id2 = nodes.len();
+                nodes.push(VisibilityNode { position: t2, on_circle: Some(j) });
+                adjacency.push(Vec::new());
+                add_edge(&mut nodes, &mut adjacency, id1, id2, &[i, j]);
+            }
+
+            let d = obstacles[i].center.distance_to(&obstacles[j].center);
+            if d > obstacles[i].radius + obstacles[j].radius {
+                for (t1, t2) in internal_tangent_points(&obstacles[i], &obstacles[j]) {
+                    let id1 = nodes.len();
+                    nodes.push(VisibilityNode { position: t1, on_circle: Some(i) });
+                    adjacency.push(Vec::new());
+                    let id2 = nodes.len();
+                    nodes.push(VisibilityNode { position: t2, on_circle: Some(j) });
+                    adjacency.push(Vec::new());
+                    add_edge(&mut nodes, &mut adjacency, id1, id2, &[i, j]);
+                }
+            }
+        }
+    }
+
+    let node_count = nodes.len();
+
+    for (i, circle) in obstacles.iter().enumerate() {
+        if circle.radius == f64::INFINITY {
+            continue;
+        }
+        let others: Vec<Circle> = obstacles
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, c)| c.clone())
+            .collect();
+        let (_, gaps) = calculate_boundary_coverage(circle, &others);
+        if gaps.is_empty() {
+            continue;
+        }
+
+        let on_circle_nodes: Vec<(usize, f64)> = nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.on_circle == Some(i))
+            .map(|(id, n)| (id, normalize_angle(circle.center.angle_to(&n.position))))
+            .collect();
+
+        for (gap_start, gap_end) in &gaps {
+            let mut in_gap: Vec<(usize, f64)> = on_circle_nodes
+                .iter()
+                .cloned()
+                .filter(|(_, angle)| *angle >= *gap_start - 1e-9 && *angle <= *gap_end + 1e-9)
+                .collect();
+            in_gap.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+
+            for pair in in_gap.windows(2) {
+                let (id_a, angle_a) = pair[0];
+                let (id_b, angle_b) = pair[1];
+                let cost = circle.radius * (angle_b - angle_a);
+                adjacency[id_a].push((id_b, cost));
+                adjacency[id_b].push((id_a, cost));
+            }
+
+            // A gap spanning the whole circle (no other obstacle buries any of its boundary)
+            // is a genuine loop - also close it by connecting the last sorted tangent point
+            // back to the first across the 0/2*PI seam.
+            if *gap_start < 1e-9 && *gap_end > 2.0 * PI - 1e-9 && in_gap.len() > 1 {
+                let (first_id, first_angle) = in_gap[0];
+                let (last_id, last_angle) = in_gap[in_gap.len() - 1];
+                let wrap_cost = circle.radius * (2.0 * PI - last_angle + first_angle);
+                adjacency[first_id].push((last_id, wrap_cost));
+                adjacency[last_id].push((first_id, wrap_cost));
+            }
+        }
+    }
+
+    let mut distances = vec![f64::INFINITY; node_count];
+    let mut came_from: HashMap<usize, usize> = HashMap::new();
+    let mut visited: HashSet<usize> = HashSet::new();
+    let mut open_set = BinaryHeap::new();
+
+    distances[START] = 0.0;
+    open_set.push(VisibilitySearchNode { id: START, cost: 0.0 });
+
+    while let Some(current) = open_set.pop() {
+        if current.id == GOAL {
+            break;
+        }
+        if !visited.insert(current.id) {
+            continue;
+        }
+
+        for &(neighbor, edge_cost) in &adjacency[current.id] {
+            if visited.contains(&neighbor) {
+                continue;
+            }
+            let tentative = distances[current.id] + edge_cost;
+            if tentative < distances[neighbor] {
+                distances[neighbor] = tentative;
+                came_from.insert(neighbor, current.id);
+                open_set.push(VisibilitySearchNode { id: neighbor, cost: tentative });
+            }
+        }
+    }
+
+    if distances[GOAL].is_infinite() {
+        return VisibilityPathResult::new(vec![], 0.0, false);
+    }
+
+    let mut node_sequence = vec![GOAL];
+    let mut current = GOAL;
+    while let Some(&parent) = came_from.get(&current) {
+        node_sequence.push(parent);
+        current = parent;
+    }
+    node_sequence.reverse();
+
+    let path: Vec<Point> = node_sequence.iter().map(|&id| nodes[id].position.clone()).collect();
+    VisibilityPathResult::new(path, distances[GOAL], true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::AgentState;
+
+    #[test]
+    fn test_manhattan_distance() {
+        let a = GridNode::new(0, 0);
+        let b = GridNode::new(3, 4);
+        assert_eq!(manhattan_distance(&a, &b), 7.0);
+        
+        let c = GridNode::new(2, 2);
+        let d = GridNode::new(2, 2);
+        assert_eq!(manhattan_distance(&c, &d), 0.0);
+    }
+
+    #[test]
+    fn test_get_neighbors() {
+        let grid_config = GridConfig::new(5, 5, (-10.0, 10.0, -10.0, 10.0), 1.0, 1000.0);
+        
+        // Test center node
+        let center = GridNode::new(2, 2);
+        let neighbors = get_neighbors(&center, &grid_config);
+        assert_eq!(neighbors.len(), 4);
+        
+        // Test corner node
+        let corner = GridNode::new(0, 0);
+        let corner_neighbors = get_neighbors(&corner, &grid_config);
+        assert_eq!(corner_neighbors.len(), 2);
+        
+        // Test edge node
+        let edge = GridNode::new(0, 2);
+        let edge_neighbors = get_neighbors(&edge, &grid_config);
+        assert_eq!(edge_neighbors.len(), 3);
+    }
+
+    #[test]
+    fn test_threat_map_generation() {
+        let grid_config = GridConfig::new(10, 10, (-5.0, 5.0, -5.0, 5.0), 1.0, 1000.0);
+        let sim_config = SimConfig::new(0.1, 1.0, 2.0, 1.0, 0.1, 10.0, 1.0, 1.0, 0.5, 1, 0, 0.0, 0.0);
+        
+        let defender = AgentState::new(Point::new(0.0, 0.0), Point::new(0.0, 0.0));
+        let intruder = AgentState::new(Point::new(3.0, 0.0), Point::new(0.0, 0.0));
+        let protected_zone = Circle::new(Point::new(-2.0, 0.0), 1.0);
+        
+        let world_state = WorldState::new(
+            vec![defender],
+            intruder,
+            protected_zone,
+        );
+        
+        let cost_map = generate_threat_map(&world_state, &grid_config, &sim_config);
+        
+        // Verify map dimensions
+        assert_eq!(cost_map.len(), 10);
+        assert_eq!(cost_map[0].len(), 10);
+        
+        // Check that base costs are applied
+        let mut has_base_cost = false;
+        let mut has_threat_cost = false;
+        
+        for row in &cost_map {
+            for &cost in row {
+                if (cost - grid_config.base_cost).abs() < 1e-10 {
+                    has_base_cost = true;
+                }
+                if cost > grid_config.base_cost + 100.0 {
+                    has_threat_cost = true;
+                }
+            }
+        }
+        
+        assert!(has_base_cost);
+        assert!(has_threat_cost);
+    }
+
+    #[test]
+    fn test_threat_map_marks_obstacle_segment_as_impassable() {
+        let grid_config = GridConfig::new(10, 10, (-5.0, 5.0, -5.0, 5.0), 1.0, 1000.0);
+        let sim_config = SimConfig::new(0.1, 1.0, 2.0, 1.0, 0.1, 10.0, 1.0, 1.0, 0.5, 1, 0, 0.0, 0.0);
+
+        let defender = AgentState::new(Point::new(-4.0, 0.0), Point::new(0.0, 0.0));
+        let intruder = AgentState::new(Point::new(4.0, 0.0), Point::new(0.0, 0.0));
+        let protected_zone = Circle::new(Point::new(-2.0, 0.0), 1.0);
+
+        let mut world_state = WorldState::new(vec![defender], intruder, protected_zone);
+        let wall_cell = GridNode::new(5, 5);
+        let wall_world_pos = to_world_coords(&wall_cell, &grid_config);
+        world_state.obstacles = vec![Segment::new(
+            Point::new(wall_world_pos.x, wall_world_pos.y - 2.0),
+            Point::new(wall_world_pos.x, wall_world_pos.y + 2.0),
+        )];
+
+        let cost_map = generate_threat_map(&world_state, &grid_config, &sim_config);
+
+        assert_eq!(cost_map[wall_cell.row][wall_cell.col], f64::INFINITY);
+    }
+
+    #[test]
+    fn test_threat_map_skips_defender_contribution_behind_a_wall() {
+        let grid_config = GridConfig::new(10, 10, (-5.0, 5.0, -5.0, 5.0), 1.0, 1000.0);
+        let sim_config = SimConfig::new(0.1, 1.0, 2.0, 1.0, 0.1, 10.0, 1.0, 1.0, 0.5, 1, 0, 0.0, 0.0);
+
+        // Defender at the origin, intruder at (3, 0), speed ratio 0.5: the Apollonian circle
+        // (center (-1, 0), radius 2) spans roughly x in [-3, 1], straddling a wall at x = -0.5.
+        let defender = AgentState::new(Point::new(0.0, 0.0), Point::new(0.0, 0.0));
+        let intruder = AgentState::new(Point::new(3.0, 0.0), Point::new(0.0, 0.0));
+        let protected_zone = Circle::new(Point::new(-2.0, 0.0), 1.0);
+
+        let mut world_state = WorldState::new(vec![defender], intruder, protected_zone);
+        world_state.obstacles = vec![Segment::new(Point::new(-0.5, -5.0), Point::new(-0.5, 5.0))];
+        let cost_map = generate_threat_map(&world_state, &grid_config, &sim_config);
+
+        // Inside the circle, on the far side of the wall from the defender: no line of sight.
+        let blocked = to_grid_coords(&Point::new(-2.0, 0.3), &grid_config).unwrap();
+        // Inside the circle, same side as the defender: sight line never crosses the wall.
+        let visible = to_grid_coords(&Point::new(0.3, 0.3), &grid_config).unwrap();
+
+        assert!((cost_map[blocked.row][blocked.col] - grid_config.base_cost).abs() < 1e-10);
+        assert!(cost_map[visible.row][visible.col] > grid_config.base_cost + 100.0);
+    }
+
+    #[test]
+    fn test_threat_map_marks_forbidden_region_as_impassable() {
+        let grid_config = GridConfig::new(10, 10, (-5.0, 5.0, -5.0, 5.0), 1.0, 1000.0);
+        let sim_config = SimConfig::new(0.1, 1.0, 2.0, 1.0, 0.1, 10.0, 1.0, 1.0, 0.5, 1, 0, 0.0, 0.0);
+
+        let defender = AgentState::new(Point::new(-4.0, 0.0), Point::new(0.0, 0.0));
+        let intruder = AgentState::new(Point::new(4.0, 0.0), Point::new(0.0, 0.0));
+        let protected_zone = Circle::new(Point::new(-2.0, 0.0), 1.0);
+
+        let mut world_state = WorldState::new(vec![defender], intruder, protected_zone);
+        let keep_out_cell = GridNode::new(2, 2);
+        let keep_out_center = to_world_coords(&keep_out_cell, &grid_config);
+        world_state.forbidden_regions = vec![Region::Circle(Circle::new(keep_out_center, 0.4))];
+
+        let cost_map = generate_threat_map(&world_state, &grid_config, &sim_config);
+
+        assert_eq!(cost_map[keep_out_cell.row][keep_out_cell.col], f64::INFINITY);
+
+        let far_cell = GridNode::new(8, 8);
+        assert!(cost_map[far_cell.row][far_cell.col] < f64::INFINITY);
+    }
+
+    #[test]
+    fn test_astar_simple_path() {
+        let grid_config = GridConfig::new(5, 5, (-2.5, 2.5, -2.5, 2.5), 1.0, 1000.0);
+        let cost_map = vec![vec![1.0; 5]; 5]; // Uniform cost
+        
+        let start = GridNode::new(0, 0);
+        let goal = GridNode::new(4, 4);
+        
+        let result = astar_pathfind(&start, &goal, &cost_map, &grid_config);
+        
+        assert!(result.found);
+        assert_eq!(result.path.len(), 9); // 8 steps + start position
+        assert_eq!(result.path[0], start);
+        assert_eq!(result.path[result.path.len() - 1], goal);
+    }
+
+    #[test]
+    fn test_astar_blocked_path() {
+        let grid_config = GridConfig::new(3, 3, (-1.5, 1.5, -1.5, 1.5), 1.0, 1000.0);
+        let mut cost_map = vec![vec![1.0; 3]; 3];
+        
+        // Create a completely blocked scenario - block middle column entirely
+        for row in 0..3 {
             cost_map[row][1] = f64::INFINITY;
         }
         
@@ -436,4 +1770,377 @@ mod tests {
         let blocked_result = astar_pathfind(&start, &goal, &fully_blocked, &grid_config);
         assert!(!blocked_result.found);
     }
+
+    #[test]
+    fn test_line_of_sight_clear_straight_line() {
+        let grid_config = GridConfig::new(5, 5, (-2.5, 2.5, -2.5, 2.5), 1.0, 1000.0);
+        let cost_map = vec![vec![1.0; 5]; 5];
+
+        let a = GridNode::new(0, 0);
+        let b = GridNode::new(4, 4);
+
+        assert!(line_of_sight(&a, &b, &cost_map, &grid_config).is_some());
+    }
+
+    #[test]
+    fn test_line_of_sight_blocked_by_cell_on_the_diagonal() {
+        let grid_config = GridConfig::new(5, 5, (-2.5, 2.5, -2.5, 2.5), 1.0, 1000.0);
+        let mut cost_map = vec![vec![1.0; 5]; 5];
+        // Sits squarely on the diagonal from (0,0) to (4,4).
+        cost_map[2][2] = f64::INFINITY;
+
+        let a = GridNode::new(0, 0);
+        let b = GridNode::new(4, 4);
+
+        assert!(line_of_sight(&a, &b, &cost_map, &grid_config).is_none());
+    }
+
+    #[test]
+    fn test_line_of_sight_weights_cost_by_sub_segment_length() {
+        let grid_config = GridConfig::new(1, 5, (-2.5, 2.5, -0.5, 0.5), 1.0, 1000.0);
+        let cost_map = vec![vec![1.0, 1.0, 3.0, 1.0, 1.0]];
+
+        let a = GridNode::new(0, 0);
+        let b = GridNode::new(0, 4);
+
+        // Straight horizontal line of length 4: the two end cells are only half-crossed
+        // (0.5 each), the three interior cells fully (1.0 each) -> 0.5+1+3+1+0.5 = 6.0.
+        let cost = line_of_sight(&a, &b, &cost_map, &grid_config).unwrap();
+        assert!((cost - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_theta_star_finds_straight_diagonal_on_uniform_cost() {
+        let grid_config = GridConfig::new(5, 5, (-2.5, 2.5, -2.5, 2.5), 1.0, 1000.0);
+        let cost_map = vec![vec![1.0; 5]; 5];
+
+        let start = GridNode::new(0, 0);
+        let goal = GridNode::new(4, 4);
+
+        let result = theta_star_pathfind(&start, &goal, &cost_map, &grid_config);
+
+        assert!(result.found);
+        assert_eq!(result.path[0], start);
+        assert_eq!(result.path[result.path.len() - 1], goal);
+        // Any-angle search should cut straight across the diagonal: start, goal, and nothing
+        // in between once line-of-sight straightening collapses the intermediate A* steps.
+        assert_eq!(result.path.len(), 2);
+        // Euclidean cost of the diagonal, not A*'s longer 8-step Manhattan-equivalent cost.
+        assert!((result.cost - 4.0 * (2.0_f64).sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_theta_star_routes_around_blocked_cell() {
+        let grid_config = GridConfig::new(5, 5, (-2.5, 2.5, -2.5, 2.5), 1.0, 1000.0);
+        let mut cost_map = vec![vec![1.0; 5]; 5];
+        cost_map[2][2] = f64::INFINITY; // sits on the direct diagonal shortcut
+
+        let start = GridNode::new(0, 0);
+        let goal = GridNode::new(4, 4);
+
+        let result = theta_star_pathfind(&start, &goal, &cost_map, &grid_config);
+
+        assert!(result.found);
+        assert!(!result.path.contains(&GridNode::new(2, 2)));
+    }
+
+    #[test]
+    fn test_theta_star_blocked_entirely_reports_not_found() {
+        let grid_config = GridConfig::new(3, 3, (-1.5, 1.5, -1.5, 1.5), 1.0, 1000.0);
+        let mut fully_blocked = vec![vec![f64::INFINITY; 3]; 3];
+        fully_blocked[1][0] = 1.0; // Only start position is passable
+
+        let start = GridNode::new(1, 0);
+        let goal = GridNode::new(1, 2);
+
+        let result = theta_star_pathfind(&start, &goal, &fully_blocked, &grid_config);
+        assert!(!result.found);
+    }
+
+    #[test]
+    fn test_theta_star_rejects_diagonal_step_cutting_a_sealed_corner() {
+        // (0,0) is walled in by both orthogonal neighbors: no single diagonal step to (1,1)
+        // may cut between them, matching `line_of_sight`'s supercover convention.
+        let grid_config = GridConfig::new(3, 3, (-1.5, 1.5, -1.5, 1.5), 1.0, 1000.0);
+        let mut cost_map = vec![vec![1.0; 3]; 3];
+        cost_map[0][1] = f64::INFINITY;
+        cost_map[1][0] = f64::INFINITY;
+
+        let start = GridNode::new(0, 0);
+        let goal = GridNode::new(1, 1);
+
+        let astar_result = astar_pathfind(&start, &goal, &cost_map, &grid_config);
+        assert!(!astar_result.found);
+
+        let result = theta_star_pathfind(&start, &goal, &cost_map, &grid_config);
+        assert!(!result.found);
+    }
+
+    #[test]
+    fn test_hierarchical_cache_matches_direct_astar_on_open_grid() {
+        let grid_config = GridConfig::new(20, 20, (-10.0, 10.0, -10.0, 10.0), 1.0, 1000.0);
+        let cost_map = vec![vec![1.0; 20]; 20];
+
+        let start = GridNode::new(0, 0);
+        let goal = GridNode::new(19, 19);
+
+        let direct = astar_pathfind(&start, &goal, &cost_map, &grid_config);
+        let cache = HierarchicalPathCache::new(cost_map, &grid_config, 10);
+        let hierarchical = cache.query(&start, &goal);
+
+        assert!(hierarchical.found);
+        assert!((hierarchical.cost - direct.cost).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_hierarchical_cache_mark_dirty_refreshes_entrance_edge_cost() {
+        let grid_config = GridConfig::new(20, 20, (-10.0, 10.0, -10.0, 10.0), 1.0, 1000.0);
+        let cost_map = vec![vec![1.0; 20]; 20];
+
+        let start = GridNode::new(2, 2);
+        let goal = GridNode::new(2, 17);
+
+        let mut cache = HierarchicalPathCache::new(cost_map.clone(), &grid_config, 10);
+        let before = cache.query(&start, &goal);
+        assert!(before.found);
+
+        // Raise the cost of just the single-step entrance cell joining the two column-chunks
+        // in this row's chunk band - a defender's Apollonian circle settling directly on a
+        // chokepoint - leaving every other cell unchanged. A big circle marks every chunk
+        // dirty, so this isolates whether the *entrance* edge's own cached cost tracks the new
+        // cost map, independent of the intra-chunk recompute covered elsewhere.
+        let mut blocked_map = cost_map;
+        blocked_map[4][10] = 50.0;
+        let blocking_circle = Circle::new(Point::new(0.0, 0.0), 20.0);
+        cache.mark_dirty(blocked_map, &blocking_circle);
+
+        // The refreshed entrance (19 via it before, 68 after) loses to a detour through the
+        // lower chunks' entrances at 39; a stale entrance cost would leave the cost at 19.
+        let after = cache.query(&start, &goal);
+        assert!(after.found);
+        assert!(after.cost > before.cost + 10.0);
+    }
+
+    #[test]
+    fn test_intruder_next_position_with_cache_reuses_cache_across_calls() {
+        let grid_config = GridConfig::new(10, 10, (-5.0, 5.0, -5.0, 5.0), 1.0, 1000.0);
+        let mut sim_config = SimConfig::new(0.1, 1.0, 2.0, 1.0, 0.1, 10.0, 1.0, 1.0, 0.5, 1, 0, 0.0, 0.0);
+        sim_config.pathfinding_mode = PathfindingMode::Hierarchical;
+
+        let defender = AgentState::new(Point::new(-4.0, 4.0), Point::new(0.0, 0.0));
+        let intruder = AgentState::new(Point::new(4.0, 0.0), Point::new(0.0, 0.0));
+        let protected_zone = Circle::new(Point::new(-2.0, 0.0), 1.0);
+        let world_state = WorldState::new(vec![defender], intruder, protected_zone);
+
+        let direct = calculate_intruder_next_position(&world_state, &grid_config, &sim_config)
+            .expect("direct hierarchical search should find a next position");
+
+        let mut cache = IntruderPathCache::new();
+        let first = calculate_intruder_next_position_with_cache(&world_state, &grid_config, &sim_config, &mut cache)
+            .expect("cached hierarchical search should find a next position");
+        assert!(cache.cache.is_some());
+        assert!((first.x - direct.x).abs() < 1e-9 && (first.y - direct.y).abs() < 1e-9);
+
+        // A second call against the same (unchanged) world state should reuse the cache via
+        // `mark_all_dirty`/`query` instead of rebuilding it, and land on the same answer.
+        let second = calculate_intruder_next_position_with_cache(&world_state, &grid_config, &sim_config, &mut cache)
+            .expect("second cached call should still find a next position");
+        assert!((second.x - direct.x).abs() < 1e-9 && (second.y - direct.y).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_intruder_next_position_uses_hierarchical_mode_when_selected() {
+        let grid_config = GridConfig::new(10, 10, (-5.0, 5.0, -5.0, 5.0), 1.0, 1000.0);
+        let mut sim_config = SimConfig::new(0.1, 1.0, 2.0, 1.0, 0.1, 10.0, 1.0, 1.0, 0.5, 1, 0, 0.0, 0.0);
+        sim_config.pathfinding_mode = PathfindingMode::Hierarchical;
+
+        let defender = AgentState::new(Point::new(-4.0, 4.0), Point::new(0.0, 0.0));
+        let intruder = AgentState::new(Point::new(4.0, 0.0), Point::new(0.0, 0.0));
+        let protected_zone = Circle::new(Point::new(-2.0, 0.0), 1.0);
+        let world_state = WorldState::new(vec![defender], intruder, protected_zone);
+
+        let next_position = calculate_intruder_next_position(&world_state, &grid_config, &sim_config);
+        assert!(next_position.is_some());
+
+        let full_path = calculate_intruder_full_path(&world_state, &grid_config, &sim_config);
+        assert!(full_path.found);
+        assert!(full_path.path.len() >= 2);
+    }
+
+    #[test]
+    fn test_hierarchical_cache_same_chunk_query_has_no_junction_duplicates() {
+        let grid_config = GridConfig::new(10, 10, (-5.0, 5.0, -5.0, 5.0), 1.0, 1000.0);
+        let cost_map = vec![vec![1.0; 10]; 10];
+
+        let start = GridNode::new(1, 1);
+        let goal = GridNode::new(1, 1);
+
+        let cache = HierarchicalPathCache::new(cost_map, &grid_config, 10);
+        let result = cache.query(&start, &goal);
+
+        assert!(result.found);
+        assert_eq!(result.path, vec![GridNode::new(1, 1)]);
+        assert_eq!(result.cost, 0.0);
+    }
+
+    #[test]
+    fn test_hierarchical_cache_reports_no_path_when_fully_blocked() {
+        let grid_config = GridConfig::new(10, 10, (-5.0, 5.0, -5.0, 5.0), 1.0, 1000.0);
+        let mut cost_map = vec![vec![1.0; 10]; 10];
+        for row in &mut cost_map {
+            for col in 5..10 {
+                row[col] = f64::INFINITY;
+            }
+        }
+
+        let start = GridNode::new(1, 1);
+        let goal = GridNode::new(1, 8);
+
+        let cache = HierarchicalPathCache::new(cost_map, &grid_config, 5);
+        let result = cache.query(&start, &goal);
+
+        assert!(!result.found);
+    }
+
+    #[test]
+    fn test_hierarchical_cache_mark_dirty_routes_around_new_obstacle() {
+        let grid_config = GridConfig::new(20, 20, (-10.0, 10.0, -10.0, 10.0), 1.0, 1000.0);
+        let cost_map = vec![vec![1.0; 20]; 20];
+
+        let start = GridNode::new(5, 5);
+        let goal = GridNode::new(5, 15);
+
+        let mut cache = HierarchicalPathCache::new(cost_map.clone(), &grid_config, 10);
+        let before = cache.query(&start, &goal);
+        assert!(before.found);
+
+        // Drop a huge threat penalty straight across the row the start and goal share,
+        // modelling a defender's Apollonian circle settling over the corridor.
+        let mut blocked_map = cost_map;
+        for col in 0..20 {
+            blocked_map[5][col] = 1000.0;
+        }
+        let blocking_circle = Circle::new(Point::new(0.0, 0.0), 20.0);
+        cache.mark_dirty(blocked_map, &blocking_circle);
+
+        let after = cache.query(&start, &goal);
+        assert!(after.found);
+        assert!(after.cost > before.cost);
+    }
+
+    #[test]
+    fn test_hierarchical_cache_rebuild_chunk_is_idempotent_on_unchanged_costs() {
+        let grid_config = GridConfig::new(20, 20, (-10.0, 10.0, -10.0, 10.0), 1.0, 1000.0);
+        let cost_map = vec![vec![1.0; 20]; 20];
+
+        let start = GridNode::new(2, 2);
+        let goal = GridNode::new(17, 17);
+
+        let mut cache = HierarchicalPathCache::new(cost_map, &grid_config, 10);
+        let before = cache.query(&start, &goal);
+
+        cache.rebuild_chunk(ChunkId { row: 0, col: 0 });
+        let after = cache.query(&start, &goal);
+
+        assert!(before.found && after.found);
+        assert!((before.cost - after.cost).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_visibility_graph_straight_line_when_unobstructed() {
+        let start = Point::new(-5.0, 0.0);
+        let goal = Point::new(5.0, 0.0);
+
+        let result = visibility_graph_pathfind(&start, &goal, &[]);
+
+        assert!(result.found);
+        assert_eq!(result.path.len(), 2);
+        assert!((result.cost - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_visibility_graph_routes_around_single_obstacle() {
+        let start = Point::new(-10.0, 0.0);
+        let goal = Point::new(10.0, 0.0);
+        let obstacle = Circle::new(Point::new(0.0, 0.0), 3.0);
+
+        let result = visibility_graph_pathfind(&start, &goal, &[obstacle.clone()]);
+
+        assert!(result.found);
+        // Must not pass through the obstacle's interior.
+        for window in result.path.windows(2) {
+            assert!(point_to_segment_distance(&obstacle.center, &window[0], &window[1]) >= obstacle.radius - 1e-6);
+        }
+        // Strictly longer than the direct line, since it has to detour around the circle.
+        assert!(result.cost > start.distance_to(&goal));
+    }
+
+    #[test]
+    fn test_visibility_graph_reports_no_path_when_goal_is_enclosed() {
+        // A single obstacle centered on the goal seals it off - no tangent geometry can
+        // reach it without crossing the obstacle's interior.
+        let start = Point::new(-10.0, 0.0);
+        let goal = Point::new(0.0, 0.0);
+        let sealing_obstacle = Circle::new(Point::new(0.0, 0.0), 5.0);
+
+        let result = visibility_graph_pathfind(&start, &goal, &[sealing_obstacle]);
+
+        assert!(!result.found);
+    }
+
+    #[test]
+    fn test_visibility_graph_matches_direct_distance_with_two_disjoint_obstacles_off_axis() {
+        let start = Point::new(-10.0, 0.0);
+        let goal = Point::new(10.0, 0.0);
+        // Both obstacles sit well off the direct line, so it should remain unobstructed.
+        let obstacles = vec![
+            Circle::new(Point::new(-2.0, 8.0), 2.0),
+            Circle::new(Point::new(2.0, -8.0), 2.0),
+        ];
+
+        let result = visibility_graph_pathfind(&start, &goal, &obstacles);
+
+        assert!(result.found);
+        assert!((result.cost - start.distance_to(&goal)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_visibility_graph_ignores_infinite_radius_obstacle() {
+        // An equal-speed defender's Apollonian circle degenerates to an infinite-radius
+        // "circle" (the perpendicular bisector convention used elsewhere in this module);
+        // it must not be treated as an obstacle that blocks every edge, including the
+        // direct start-goal edge.
+        let start = Point::new(-5.0, 0.0);
+        let goal = Point::new(5.0, 0.0);
+        let obstacle = Circle::new(Point::new(0.0, 100.0), f64::INFINITY);
+
+        let result = visibility_graph_pathfind(&start, &goal, &[obstacle]);
+
+        assert!(result.found);
+        assert!((result.cost - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_external_tangent_points_touch_both_circle_boundaries() {
+        let c1 = Circle::new(Point::new(0.0, 0.0), 1.0);
+        let c2 = Circle::new(Point::new(10.0, 0.0), 2.0);
+
+        let tangents = external_tangent_points(&c1, &c2);
+        assert_eq!(tangents.len(), 2);
+        for (t1, t2) in &tangents {
+            assert!((c1.center.distance_to(t1) - c1.radius).abs() < 1e-9);
+            assert!((c2.center.distance_to(t2) - c2.radius).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_internal_tangent_points_only_exist_for_disjoint_circles() {
+        let disjoint_a = Circle::new(Point::new(0.0, 0.0), 1.0);
+        let disjoint_b = Circle::new(Point::new(4.0, 0.0), 1.0);
+        assert_eq!(internal_tangent_points(&disjoint_a, &disjoint_b).len(), 2);
+
+        let overlapping_a = Circle::new(Point::new(0.0, 0.0), 2.0);
+        let overlapping_b = Circle::new(Point::new(1.0, 0.0), 2.0);
+        assert_eq!(internal_tangent_points(&overlapping_a, &overlapping_b).len(), 0);
+    }
 }
\ No newline at end of file
@@ -1,3 +1,6 @@
+use crate::geometry::Segment;
+use crate::ops::{acos, atan2, cos, sin, sqrt, FloatPow};
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use std::f64::consts::PI;
 
@@ -18,15 +21,15 @@ impl Point {
     }
 
     pub fn distance_to(&self, other: &Point) -> f64 {
-        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+        sqrt((self.x - other.x).squared() + (self.y - other.y).squared())
     }
 
     pub fn angle_to(&self, other: &Point) -> f64 {
-        (other.y - self.y).atan2(other.x - self.x)
+        atan2(other.y - self.y, other.x - self.x)
     }
 
     pub fn normalize(&self) -> Point {
-        let mag = (self.x * self.x + self.y * self.y).sqrt();
+        let mag = sqrt(self.x * self.x + self.y * self.y);
         if mag == 0.0 {
             Point { x: 0.0, y: 0.0 }
         } else {
@@ -38,7 +41,7 @@ impl Point {
     }
 
     pub fn magnitude(&self) -> f64 {
-        (self.x * self.x + self.y * self.y).sqrt()
+        sqrt(self.x * self.x + self.y * self.y)
     }
 
     pub fn __add__(&self, other: &Point) -> Point {
@@ -98,11 +101,362 @@ impl Circle {
         self.center.distance_to(point) <= self.radius
     }
 
+    /// Minimum translation vector to push `self` out of `other`, plus the penetration depth.
+    ///
+    /// The vector points from `other`'s center toward `self`'s center, scaled so that
+    /// translating `self` by it leaves the two circles exactly touching. Falls back to a
+    /// fixed direction when the centers coincide, since no direction is otherwise implied.
+    /// Returns `None` if the circles don't overlap.
+    pub fn separation(&self, other: &Circle) -> Option<(Point, f64)> {
+        if self.radius == f64::INFINITY || other.radius == f64::INFINITY {
+            return None;
+        }
+
+        let dx = self.center.x - other.center.x;
+        let dy = self.center.y - other.center.y;
+        let distance = sqrt(dx * dx + dy * dy);
+        let overlap_depth = (self.radius + other.radius) - distance;
+
+        if overlap_depth <= 0.0 {
+            return None;
+        }
+
+        let direction = if distance < 1e-10 {
+            Point::new(1.0, 0.0)
+        } else {
+            Point::new(dx / distance, dy / distance)
+        };
+
+        let mtv = Point::new(direction.x * overlap_depth, direction.y * overlap_depth);
+        Some((mtv, overlap_depth))
+    }
+
+    /// Intersect the ray `origin + t*dir` (`t` unbounded below, in units of `dir`'s own
+    /// length) with this circle, returning the near/far parameters and the near contact
+    /// point. `t_near` is negative when `origin` lies inside the circle.
+    ///
+    /// Returns `None` if `dir` is degenerate, the ray never reaches the circle (`t_far < 0`),
+    /// or it misses entirely (negative discriminant).
+    pub fn ray_intersection(&self, origin: &Point, dir: &Point) -> Option<RayHit> {
+        if self.radius == f64::INFINITY {
+            return None;
+        }
+
+        let a = dir.x.squared() + dir.y.squared();
+        if a < 1e-10 {
+            return None;
+        }
+
+        let fx = origin.x - self.center.x;
+        let fy = origin.y - self.center.y;
+        let b = 2.0 * (fx * dir.x + fy * dir.y);
+        let c = fx.squared() + fy.squared() - self.radius.squared();
+
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let sqrt_discriminant = sqrt(discriminant);
+        let mut t_near = (-b - sqrt_discriminant) / (2.0 * a);
+        let mut t_far = (-b + sqrt_discriminant) / (2.0 * a);
+        if t_near > t_far {
+            std::mem::swap(&mut t_near, &mut t_far);
+        }
+
+        if t_far < 0.0 {
+            return None;
+        }
+
+        let point_near = Point::new(origin.x + dir.x * t_near, origin.y + dir.y * t_near);
+        Some(RayHit::new(t_near, t_far, point_near))
+    }
+
+    /// The two points on this circle's boundary where a tangent line from an external
+    /// `point` touches it, defining the "escape cone" within which a ray from `point`
+    /// can reach the circle's far side without crossing it.
+    ///
+    /// Returns `None` if `point` is inside the circle or on its boundary, where no real
+    /// tangent exists.
+    pub fn tangent_lines_from(&self, point: &Point) -> Option<(Point, Point)> {
+        if self.radius == f64::INFINITY {
+            return None;
+        }
+
+        let dx = point.x - self.center.x;
+        let dy = point.y - self.center.y;
+        let d = sqrt(dx.squared() + dy.squared());
+        if d <= self.radius {
+            return None;
+        }
+
+        let center_to_point = atan2(dy, dx);
+        let half_angle = acos(self.radius / d);
+
+        let tangent_point = |angle: f64| {
+            Point::new(
+                self.center.x + self.radius * cos(angle),
+                self.center.y + self.radius * sin(angle),
+            )
+        };
+
+        Some((
+            tangent_point(center_to_point + half_angle),
+            tangent_point(center_to_point - half_angle),
+        ))
+    }
+
+    /// All common tangent segments between this circle and `other`, each pairing the touch
+    /// point on `self` with the touch point on `other` - external tangents (both circles on
+    /// the same side of the line) plus, when the circles are disjoint, internal tangents
+    /// (circles on opposite sides).
+    ///
+    /// Standard construction: with `d` the distance between centers and `base` the angle
+    /// from `self.center` to `other.center`, the external touch points sit at `base ±
+    /// acos((r1 - r2)/d)`; internal touch points at `base ± acos((r1 + r2)/d)`. No external
+    /// tangent exists when one circle contains the other (`d < |r1 - r2|`), and no internal
+    /// tangent exists unless the circles are disjoint (`d >= r1 + r2`); either `acos` argument
+    /// landing outside `[-1, 1]` signals the respective case and is skipped. When the two
+    /// touch-point angles coincide (`d == |r1 - r2|` or `d == r1 + r2`, the circles exactly
+    /// tangent to each other) only the single resulting tangency is returned, not two copies.
+    pub fn tangent_lines(&self, other: &Circle) -> Vec<(Point, Point)> {
+        if self.radius == f64::INFINITY || other.radius == f64::INFINITY {
+            return vec![];
+        }
+
+        let d = self.center.distance_to(&other.center);
+        if d < 1e-9 {
+            return vec![];
+        }
+
+        let base = self.center.angle_to(&other.center);
+
+        let touch_pair = |theta: f64, other_sign: f64| -> (Point, Point) {
+            let normal = Point::new(cos(theta), sin(theta));
+            (
+                Point::new(self.center.x + self.radius * normal.x, self.center.y + self.radius * normal.y),
+                Point::new(
+                    other.center.x + other_sign * other.radius * normal.x,
+                    other.center.y + other_sign * other.radius * normal.y,
+                ),
+            )
+        };
+
+        let mut tangents = Vec::new();
+
+        for (cos_angle, other_sign) in [((self.radius - other.radius) / d, 1.0), ((self.radius + other.radius) / d, -1.0)] {
+            if cos_angle.abs() > 1.0 {
+                continue;
+            }
+            let ang = acos(cos_angle);
+            tangents.push(touch_pair(base + ang, other_sign));
+            if ang > 1e-9 && ang < PI - 1e-9 {
+                tangents.push(touch_pair(base - ang, other_sign));
+            }
+        }
+
+        tangents
+    }
+
+    /// Every point where the infinite line through `a` and `b` crosses this circle's
+    /// boundary - zero, one (tangent), or two points.
+    ///
+    /// Writes the line through `a` and `b` in implicit form `ca*x + cb*y + cc = 0` with
+    /// `ca = b.y - a.y`, `cb = a.x - b.x`, `cc = b.x*a.y - a.x*b.y`, then substitutes into the
+    /// circle equation. When `|cb|` isn't too small the substitution solves a quadratic in
+    /// `x` (with `y` recovered from the line equation afterward); otherwise - a near-vertical
+    /// line, where solving for `x` would divide by a near-zero `cb` - it solves the symmetric
+    /// quadratic in `y` instead. Both branches reduce to `A*t^2 + B*t + C = 0` with the same
+    /// `A = ca^2 + cb^2`; the discriminant `B^2 - 4*A*C` then gives zero, one, or two roots.
+    pub fn line_intersections(&self, a: &Point, b: &Point) -> Vec<Point> {
+        if self.radius == f64::INFINITY {
+            return vec![];
+        }
+
+        const EPS: f64 = 1e-9;
+        let ca = b.y - a.y;
+        let cb = a.x - b.x;
+        let cc = b.x * a.y - a.x * b.y;
+        let (h, k, r) = (self.center.x, self.center.y, self.radius);
+
+        let big_a = ca * ca + cb * cb;
+        if big_a < EPS {
+            return vec![]; // `a` and `b` coincide: no line is defined.
+        }
+
+        let roots = |big_b: f64, big_c: f64| -> Vec<f64> {
+            let discriminant = big_b * big_b - 4.0 * big_a * big_c;
+            if discriminant < -EPS {
+                vec![]
+            } else if discriminant.abs() <= EPS {
+                vec![-big_b / (2.0 * big_a)]
+            } else {
+                let sqrt_discriminant = sqrt(discriminant);
+                vec![(-big_b - sqrt_discriminant) / (2.0 * big_a), (-big_b + sqrt_discriminant) / (2.0 * big_a)]
+            }
+        };
+
+        if cb.abs() >= EPS {
+            let q = cc + k * cb;
+            let big_b = 2.0 * (ca * q - h * cb * cb);
+            let big_c = cb * cb * (h * h - r * r) + q * q;
+
+            roots(big_b, big_c)
+                .into_iter()
+                .map(|x| Point::new(x, -(ca * x + cc) / cb))
+                .collect()
+        } else {
+            let q = cc + h * ca;
+            let big_b = 2.0 * (cb * q - k * ca * ca);
+            let big_c = ca * ca * (k * k - r * r) + q * q;
+
+            roots(big_b, big_c)
+                .into_iter()
+                .map(|y| Point::new(-(cb * y + cc) / ca, y))
+                .collect()
+        }
+    }
+
+    /// Like `line_intersections`, but restricted to the finite segment `a`-`b`: any root
+    /// whose coordinates fall outside the segment's bounding box (within a small epsilon) is
+    /// dropped. Lets a caller test whether a planned defender/intruder path crosses an
+    /// Apollonian circle, and exactly where, for pursuit-evasion event detection.
+    pub fn segment_intersections(&self, a: &Point, b: &Point) -> Vec<Point> {
+        const EPS: f64 = 1e-9;
+        let (min_x, max_x) = (a.x.min(b.x) - EPS, a.x.max(b.x) + EPS);
+        let (min_y, max_y) = (a.y.min(b.y) - EPS, a.y.max(b.y) + EPS);
+
+        self.line_intersections(a, b)
+            .into_iter()
+            .filter(|p| p.x >= min_x && p.x <= max_x && p.y >= min_y && p.y <= max_y)
+            .collect()
+    }
+
     pub fn __repr__(&self) -> String {
         format!("Circle(center={}, radius={:.3})", self.center.__repr__(), self.radius)
     }
 }
 
+/// A ray-circle intersection, carrying the hit parameters along the ray rather than just
+/// the contact point, so callers can distinguish near/far crossings or reject hits behind
+/// the ray's origin.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct RayHit {
+    #[pyo3(get, set)]
+    pub t_near: f64,
+    #[pyo3(get, set)]
+    pub t_far: f64,
+    #[pyo3(get, set)]
+    pub point_near: Point,
+}
+
+#[pymethods]
+impl RayHit {
+    #[new]
+    pub fn new(t_near: f64, t_far: f64, point_near: Point) -> Self {
+        RayHit { t_near, t_far, point_near }
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "RayHit(t_near={:.3}, t_far={:.3}, point_near={})",
+            self.t_near, self.t_far, self.point_near.__repr__()
+        )
+    }
+}
+
+/// An axis-aligned bounding box, used for broad-phase pruning before exact geometry tests
+/// (e.g. `Annulus`'s `fast_disjoint`/`fast_contains`). Not exposed to Python - a plain
+/// geometric helper like `Region` or `Arc`, not a user-facing result type.
+#[derive(Debug, Clone)]
+pub struct AABB {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl AABB {
+    pub fn new(min: Point, max: Point) -> Self {
+        AABB { min, max }
+    }
+
+    pub fn corners(&self) -> [Point; 4] {
+        [
+            Point::new(self.min.x, self.min.y),
+            Point::new(self.max.x, self.min.y),
+            Point::new(self.min.x, self.max.y),
+            Point::new(self.max.x, self.max.y),
+        ]
+    }
+}
+
+/// A defender's capture region bounded by both a minimum standoff (`inner`) and a maximum
+/// reach (`outer`) - the single-`Circle` Apollonian model can express unbounded or
+/// maximum-range capture, but not a minimum range a slow-to-turn or minimum-engagement-range
+/// defender can't cover.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct Annulus {
+    #[pyo3(get, set)]
+    pub center: Point,
+    #[pyo3(get, set)]
+    pub inner: f64,
+    #[pyo3(get, set)]
+    pub outer: f64,
+}
+
+#[pymethods]
+impl Annulus {
+    #[new]
+    pub fn new(center: Point, inner: f64, outer: f64) -> Self {
+        Annulus { center, inner, outer }
+    }
+
+    pub fn contains_point(&self, point: &Point) -> bool {
+        let distance = self.center.distance_to(point);
+        distance >= self.inner && distance <= self.outer
+    }
+
+    /// Broad-phase test: true when `aabb` cannot possibly overlap the annulus - either it
+    /// lies entirely outside the outer circle, or entirely inside the inner circle. The
+    /// minimum distance from `center` to any point in an axis-aligned box is the distance to
+    /// the box's nearest point (`center`'s coordinates clamped into the box); the maximum is
+    /// always achieved at one of its four corners, since distance-to-point is convex.
+    pub fn fast_disjoint(&self, aabb: &AABB) -> bool {
+        let closest_x = self.center.x.max(aabb.min.x).min(aabb.max.x);
+        let closest_y = self.center.y.max(aabb.min.y).min(aabb.max.y);
+        let min_distance = self.center.distance_to(&Point::new(closest_x, closest_y));
+        if min_distance > self.outer {
+            return true;
+        }
+
+        let max_distance = aabb
+            .corners()
+            .iter()
+            .map(|corner| self.center.distance_to(corner))
+            .fold(0.0_f64, f64::max);
+        max_distance < self.inner
+    }
+
+    /// Broad-phase test: true when every corner of `aabb` falls within `[inner, outer]` of
+    /// `center`. Corners bound the outer side exactly (the box's farthest point from `center`
+    /// is always a corner) but not the inner one: an edge can pass closer to `center` than
+    /// any corner, so a true result does not guarantee the whole box avoids the inner hole.
+    pub fn fast_contains(&self, aabb: &AABB) -> bool {
+        aabb.corners().iter().all(|corner| {
+            let distance = self.center.distance_to(corner);
+            distance >= self.inner && distance <= self.outer
+        })
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "Annulus(center={}, inner={:.3}, outer={:.3})",
+            self.center.__repr__(), self.inner, self.outer
+        )
+    }
+}
+
 #[pyclass]
 #[derive(Clone, Debug)]
 pub struct AgentState {
@@ -137,6 +491,16 @@ pub struct WorldState {
     pub intruder: AgentState,
     #[pyo3(get, set)]
     pub protected_zone: Circle,
+    /// Additional no-go geometry (obstacles, keep-out rings) layered on top of
+    /// `protected_zone`; any cell inside one of these regions is marked impassable by
+    /// `generate_threat_map`. Not exposed to Python: `Region` is a composable algebraic type
+    /// with no direct Python representation, so it's populated from Rust rather than through
+    /// the constructor.
+    pub forbidden_regions: Vec<Region>,
+    /// Static walls/terrain edges that physically block movement and line of sight (urban or
+    /// maze scenarios). Not exposed to Python for the same reason as `forbidden_regions`;
+    /// consumed by `generate_threat_map` and `has_line_of_sight`.
+    pub obstacles: Vec<Segment>,
 }
 
 #[pymethods]
@@ -147,6 +511,8 @@ impl WorldState {
             defenders,
             intruder,
             protected_zone,
+            forbidden_regions: Vec::new(),
+            obstacles: Vec::new(),
         }
     }
 
@@ -173,6 +539,25 @@ pub struct SimConfig {
     pub w_repel: f64,  // Weight for overlap penalty
     #[pyo3(get, set)]
     pub epsilon: f64,  // Overlap tolerance
+    #[pyo3(get, set)]
+    pub max_force: f64,  // Maximum acceleration applied per step (steering layer)
+    #[pyo3(get, set)]
+    pub slowing_radius: f64,  // Distance from a target at which "arrive" begins decelerating
+    #[pyo3(get, set)]
+    pub dt: f64,  // Simulation timestep, used to predict next-step positions
+    #[pyo3(get, set)]
+    pub collision_radius: f64,  // Minimum separation maintained between defenders
+    pub collision_mode: CollisionMode,  // How predicted defender collisions are resolved
+    #[pyo3(get, set)]
+    pub dwell_steps: u32,  // Steps a transition's triggering condition must hold before it fires
+    #[pyo3(get, set)]
+    pub intercept_cooldown_steps: u32,  // Steps interception may stay infeasible before de-committing
+    pub intruder_strategy: IntruderStrategy,  // Evasion policy used by compute_intruder_velocity
+    #[pyo3(get, set)]
+    pub zigzag_amplitude: f64,  // Lateral offset at the zig-zag's peak, as a fraction of forward speed
+    #[pyo3(get, set)]
+    pub zigzag_wavelength: f64,  // Distance-to-goal traveled per full zig-zag cycle
+    pub pathfinding_mode: PathfindingMode,  // Grid search used to route the intruder through the threat map
 }
 
 #[pymethods]
@@ -184,6 +569,14 @@ impl SimConfig {
         intruder_speed: f64,
         w_repel: f64,
         epsilon: f64,
+        max_force: f64,
+        slowing_radius: f64,
+        dt: f64,
+        collision_radius: f64,
+        dwell_steps: u32,
+        intercept_cooldown_steps: u32,
+        zigzag_amplitude: f64,
+        zigzag_wavelength: f64,
     ) -> Self {
         SimConfig {
             learning_rate,
@@ -191,12 +584,89 @@ impl SimConfig {
             intruder_speed,
             w_repel,
             epsilon,
+            max_force,
+            slowing_radius,
+            dt,
+            collision_radius,
+            collision_mode: CollisionMode::Slide,
+            dwell_steps,
+            intercept_cooldown_steps,
+            intruder_strategy: IntruderStrategy::Direct,
+            zigzag_amplitude,
+            zigzag_wavelength,
+            pathfinding_mode: PathfindingMode::AStar,
         }
     }
 
     pub fn speed_ratio(&self) -> f64 {
         self.defender_speed / self.intruder_speed
     }
+
+    /// How `avoid_defender_collisions` resolves a predicted collision: `"stop"` or `"slide"`
+    /// (the default). The underlying `CollisionMode` isn't itself exposed to Python, so this
+    /// accessor is the only way to select STOP mode from a caller of the library.
+    #[getter]
+    fn collision_mode(&self) -> &'static str {
+        match self.collision_mode {
+            CollisionMode::Stop => "stop",
+            CollisionMode::Slide => "slide",
+        }
+    }
+
+    #[setter]
+    fn set_collision_mode(&mut self, mode: &str) -> PyResult<()> {
+        self.collision_mode = match mode {
+            "stop" => CollisionMode::Stop,
+            "slide" => CollisionMode::Slide,
+            _ => return Err(PyValueError::new_err(format!("unknown collision_mode: {mode:?}"))),
+        };
+        Ok(())
+    }
+
+    /// Evasion policy used by `compute_intruder_velocity`: `"direct"` (the default) or
+    /// `"zigzag"`. The underlying `IntruderStrategy` isn't itself exposed to Python, so this
+    /// accessor is the only way to turn ZigZag on from a caller of the library.
+    #[getter]
+    fn intruder_strategy(&self) -> &'static str {
+        match self.intruder_strategy {
+            IntruderStrategy::Direct => "direct",
+            IntruderStrategy::ZigZag => "zigzag",
+        }
+    }
+
+    #[setter]
+    fn set_intruder_strategy(&mut self, strategy: &str) -> PyResult<()> {
+        self.intruder_strategy = match strategy {
+            "direct" => IntruderStrategy::Direct,
+            "zigzag" => IntruderStrategy::ZigZag,
+            _ => return Err(PyValueError::new_err(format!("unknown intruder_strategy: {strategy:?}"))),
+        };
+        Ok(())
+    }
+
+    /// Grid search `calculate_intruder_next_position`/`calculate_intruder_full_path` use to
+    /// route the intruder: `"astar"` (the default), `"theta_star"`, or `"hierarchical"`. The
+    /// underlying `PathfindingMode` isn't itself exposed to Python, so this accessor is the
+    /// only way to select Theta*/hierarchical search from a caller of the library.
+    #[getter]
+    fn pathfinding_mode(&self) -> &'static str {
+        match self.pathfinding_mode {
+            PathfindingMode::AStar => "astar",
+            PathfindingMode::ThetaStar => "theta_star",
+            PathfindingMode::Hierarchical => "hierarchical",
+        }
+    }
+
+    #[setter]
+    fn set_pathfinding_mode(&mut self, mode: &str) -> PyResult<()> {
+        self.pathfinding_mode = match mode {
+            "astar" => PathfindingMode::AStar,
+            "theta_star" => PathfindingMode::ThetaStar,
+            "hierarchical" => PathfindingMode::Hierarchical,
+            _ => return Err(PyValueError::new_err(format!("unknown pathfinding_mode: {mode:?}"))),
+        };
+        Ok(())
+    }
 }
 
 // Internal enums (not exposed to Python)
@@ -204,6 +674,155 @@ impl SimConfig {
 pub enum ControlState {
     Travel,
     Engage,
+    /// Committed to a predicted interception; can de-commit back to Engage/Travel if
+    /// interception becomes infeasible for longer than `SimConfig::intercept_cooldown_steps`.
+    Intercept,
+    /// The defender's Apollonian circle no longer separates the intruder from the
+    /// protected zone; steer back toward the goal to re-establish a guarding position.
+    Retreat,
+}
+
+/// Per-defender FSM bookkeeping carried between simulation steps: the current control
+/// state plus the dwell/cooldown counters that give the FSM hysteresis, so it doesn't
+/// chatter between states near a boundary condition.
+#[pyclass]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DefenderFsmState {
+    pub control_state: ControlState,
+    #[pyo3(get, set)]
+    pub dwell_steps: u32,
+    #[pyo3(get, set)]
+    pub intercept_infeasible_steps: u32,
+}
+
+#[pymethods]
+impl DefenderFsmState {
+    #[new]
+    pub fn new() -> Self {
+        DefenderFsmState {
+            control_state: ControlState::Travel,
+            dwell_steps: 0,
+            intercept_infeasible_steps: 0,
+        }
+    }
+}
+
+impl Default for DefenderFsmState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How pairs of defenders resolve a predicted collision in `avoid_defender_collisions`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollisionMode {
+    /// Cancel the pair's closing speed along the axis between them, half from each defender.
+    Stop,
+    /// Remove only the approaching component, sliding tangentially around the neighbor.
+    Slide,
+}
+
+/// Evasion policy used by `compute_intruder_velocity`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntruderStrategy {
+    /// Head straight at the protected zone's center.
+    Direct,
+    /// Direct, plus a periodic lateral offset to skirt defender coverage.
+    ZigZag,
+}
+
+/// Which grid search `calculate_intruder_next_position`/`calculate_intruder_full_path` use
+/// to route the intruder through the threat map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathfindingMode {
+    /// 4-connected grid search (`astar_pathfind`).
+    AStar,
+    /// 8-connected any-angle search with line-of-sight smoothing (`theta_star_pathfind`).
+    ThetaStar,
+    /// Two-level HPA*-style search (`HierarchicalPathCache`) - builds the chunk-based
+    /// abstract graph fresh from the current threat map each call, trading the full-grid
+    /// search for several small per-chunk ones. Cheaper than `AStar`/`ThetaStar` as the grid
+    /// grows large; a caller that re-plans every tick without the threat map changing much
+    /// should keep its own `HierarchicalPathCache` and call `mark_dirty`/`query` directly
+    /// instead, to also avoid rebuilding the abstract graph from scratch.
+    Hierarchical,
+}
+
+/// Result of `visibility_graph_pathfind`: a continuous-space analogue of `PathResult` whose
+/// waypoints are world `Point`s (tangent points and arc vertices around obstacle circles)
+/// rather than grid cells.
+#[pyclass]
+#[derive(Clone, Debug)]
+pub struct VisibilityPathResult {
+    #[pyo3(get, set)]
+    pub path: Vec<Point>,
+    #[pyo3(get, set)]
+    pub cost: f64,
+    #[pyo3(get, set)]
+    pub found: bool,
+}
+
+#[pymethods]
+impl VisibilityPathResult {
+    #[new]
+    pub fn new(path: Vec<Point>, cost: f64, found: bool) -> Self {
+        VisibilityPathResult { path, cost, found }
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "VisibilityPathResult(waypoints={}, cost={:.3}, found={})",
+            self.path.len(), self.cost, self.found
+        )
+    }
+}
+
+/// A composable region of the plane, built from primitives (`Circle`, `HalfPlane`) and
+/// boolean/affine combinators, answering point-membership and signed-distance queries.
+///
+/// Lets callers model compound keep-out or keep-in geometry - e.g. an annular defense ring
+/// (`Difference` of an outer and inner `Circle`) or a zone carved up by obstacles - without
+/// every consumer needing its own bespoke combination logic.
+#[derive(Debug, Clone)]
+pub enum Region {
+    Circle(Circle),
+    /// The half of the plane where `normal·p <= offset` (`normal` need not be unit length).
+    HalfPlane { normal: Point, offset: f64 },
+    Union(Box<Region>, Box<Region>),
+    Intersection(Box<Region>, Box<Region>),
+    /// Everything in the first region that is not in the second.
+    Difference(Box<Region>, Box<Region>),
+    Translate(Box<Region>, Point),
+}
+
+impl Region {
+    pub fn contains_point(&self, point: &Point) -> bool {
+        self.signed_distance(point) >= 0.0
+    }
+
+    /// Positive inside the region, negative outside, zero on the boundary. Exact for `Circle`
+    /// and `HalfPlane`; for combinators this is the standard CSG combination of the operands'
+    /// signed distances (with our sign convention, inside is positive rather than negative),
+    /// which is an exact boundary predicate but only an approximate *distance* away from it.
+    pub fn signed_distance(&self, point: &Point) -> f64 {
+        match self {
+            Region::Circle(circle) => circle.radius - circle.center.distance_to(point),
+            Region::HalfPlane { normal, offset } => {
+                let magnitude = sqrt(normal.x * normal.x + normal.y * normal.y);
+                if magnitude < 1e-10 {
+                    return 0.0;
+                }
+                (offset - (normal.x * point.x + normal.y * point.y)) / magnitude
+            }
+            Region::Union(a, b) => a.signed_distance(point).max(b.signed_distance(point)),
+            Region::Intersection(a, b) => a.signed_distance(point).min(b.signed_distance(point)),
+            Region::Difference(a, b) => a.signed_distance(point).min(-b.signed_distance(point)),
+            Region::Translate(region, offset) => {
+                let shifted = Point::new(point.x - offset.x, point.y - offset.y);
+                region.signed_distance(&shifted)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -225,14 +844,442 @@ impl Arc {
         length
     }
 
-    pub fn overlaps(&self, other: &Arc) -> f64 {
-        // Calculate overlap between two arcs (simplified implementation)
-        let start = self.start_angle.max(other.start_angle);
-        let end = self.end_angle.min(other.end_angle);
-        if start <= end {
-            end - start
+    /// Split this arc into one or two sub-arcs, each fully contained in `[0, 2*PI)`,
+    /// cutting at the `2*PI` seam if the arc wraps around it.
+    pub(crate) fn normalized_segments(&self) -> Vec<(f64, f64)> {
+        let normalize = |angle: f64| {
+            let mut a = angle % (2.0 * PI);
+            if a < 0.0 {
+                a += 2.0 * PI;
+            }
+            a
+        };
+
+        let start = normalize(self.start_angle);
+        let end = start + self.length();
+
+        if end <= 2.0 * PI + 1e-9 {
+            vec![(start, end.min(2.0 * PI))]
         } else {
-            0.0
+            vec![(start, 2.0 * PI), (0.0, end - 2.0 * PI)]
+        }
+    }
+
+    /// Length of the overlap between two arcs, correct even when either wraps past `2*PI`.
+    pub fn overlaps(&self, other: &Arc) -> f64 {
+        let mut total = 0.0;
+        for a in self.normalized_segments() {
+            for b in other.normalized_segments() {
+                total += (a.1.min(b.1) - a.0.max(b.0)).max(0.0);
+            }
+        }
+        total
+    }
+
+    /// Merge a set of arcs (which may overlap and may wrap past `2*PI`) into the minimal set
+    /// of disjoint arcs covering the same region: normalize and split every arc at the seam,
+    /// sort by start angle, then sweep-merge overlapping or touching segments. If the result
+    /// touches both ends of `[0, 2*PI)` it's stitched back into a single wrapping arc, so a
+    /// ring fully covered by arcs on both sides of the seam collapses to one entry.
+    pub fn union(arcs: &[Arc]) -> Vec<Arc> {
+        let mut segments: Vec<(f64, f64)> = arcs.iter().flat_map(|arc| arc.normalized_segments()).collect();
+        if segments.is_empty() {
+            return Vec::new();
+        }
+        segments.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut merged: Vec<(f64, f64)> = Vec::new();
+        for (start, end) in segments {
+            if let Some(last) = merged.last_mut() {
+                if start <= last.1 + 1e-9 {
+                    last.1 = last.1.max(end);
+                    continue;
+                }
+            }
+            merged.push((start, end));
+        }
+
+        // Stitch the seam: a segment ending at 2*PI and one starting at 0 are really one
+        // contiguous arc that happens to wrap.
+        if merged.len() > 1 {
+            let first = merged[0];
+            let last_idx = merged.len() - 1;
+            let last = merged[last_idx];
+            if first.0 <= 1e-9 && (2.0 * PI - last.1).abs() < 1e-9 {
+                merged[0] = (last.0, first.1);
+                merged.remove(last_idx);
+            }
+        }
+
+        merged.into_iter().map(|(start, end)| Arc::new(start, end)).collect()
+    }
+
+    /// Fraction of the full circle (`[0, 2*PI)`) covered by the union of `arcs`.
+    pub fn coverage_fraction(arcs: &[Arc]) -> f64 {
+        let total: f64 = Arc::union(arcs).iter().map(Arc::length).sum();
+        (total / (2.0 * PI)).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_separation_returns_mtv_scaled_by_overlap_depth() {
+        let c1 = Circle::new(Point::new(0.0, 0.0), 2.0);
+        let c2 = Circle::new(Point::new(3.0, 0.0), 2.0);
+
+        let (mtv, overlap_depth) = c1.separation(&c2).unwrap();
+
+        // Combined radius 4, distance 3 -> overlap depth 1, pointing from c2 toward c1 (-x).
+        assert!((overlap_depth - 1.0).abs() < 1e-10);
+        assert!((mtv.x - (-1.0)).abs() < 1e-10);
+        assert!(mtv.y.abs() < 1e-10);
+
+        // Translating c1 by the MTV should leave the circles exactly touching.
+        let separated = Point::new(c1.center.x + mtv.x, c1.center.y + mtv.y);
+        assert!((separated.distance_to(&c2.center) - (c1.radius + c2.radius)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_separation_none_when_not_overlapping() {
+        let c1 = Circle::new(Point::new(0.0, 0.0), 1.0);
+        let c2 = Circle::new(Point::new(5.0, 0.0), 1.0);
+
+        assert!(c1.separation(&c2).is_none());
+    }
+
+    #[test]
+    fn test_separation_coincident_centers_falls_back_to_fixed_direction() {
+        let c1 = Circle::new(Point::new(1.0, 1.0), 2.0);
+        let c2 = Circle::new(Point::new(1.0, 1.0), 2.0);
+
+        let (mtv, overlap_depth) = c1.separation(&c2).unwrap();
+
+        assert!((overlap_depth - 4.0).abs() < 1e-10);
+        assert!((mtv.x - 4.0).abs() < 1e-10);
+        assert!(mtv.y.abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_ray_intersection_from_outside() {
+        let circle = Circle::new(Point::new(5.0, 0.0), 1.0);
+        let hit = circle
+            .ray_intersection(&Point::new(0.0, 0.0), &Point::new(1.0, 0.0))
+            .unwrap();
+
+        assert!((hit.t_near - 4.0).abs() < 1e-9);
+        assert!((hit.t_far - 6.0).abs() < 1e-9);
+        assert!((hit.point_near.x - 4.0).abs() < 1e-9);
+        assert!(hit.point_near.y.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ray_intersection_origin_inside_has_negative_t_near() {
+        let circle = Circle::new(Point::new(0.0, 0.0), 2.0);
+        let hit = circle
+            .ray_intersection(&Point::new(1.0, 0.0), &Point::new(1.0, 0.0))
+            .unwrap();
+
+        assert!(hit.t_near < 0.0);
+        assert!(hit.t_far > 0.0);
+    }
+
+    #[test]
+    fn test_ray_intersection_misses_circle() {
+        let circle = Circle::new(Point::new(5.0, 5.0), 1.0);
+
+        assert!(circle
+            .ray_intersection(&Point::new(0.0, 0.0), &Point::new(1.0, 0.0))
+            .is_none());
+    }
+
+    #[test]
+    fn test_ray_intersection_none_when_circle_is_behind_origin() {
+        let circle = Circle::new(Point::new(-5.0, 0.0), 1.0);
+
+        assert!(circle
+            .ray_intersection(&Point::new(0.0, 0.0), &Point::new(1.0, 0.0))
+            .is_none());
+    }
+
+    #[test]
+    fn test_tangent_lines_from_external_point() {
+        let circle = Circle::new(Point::new(0.0, 0.0), 3.0);
+        let (t1, t2) = circle.tangent_lines_from(&Point::new(5.0, 0.0)).unwrap();
+
+        // Both tangent points lie on the circle and are perpendicular to the radius there.
+        for tangent in [&t1, &t2] {
+            assert!((circle.center.distance_to(tangent) - circle.radius).abs() < 1e-9);
+        }
+        assert!((t1.y - (-t2.y)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tangent_lines_from_none_when_point_inside() {
+        let circle = Circle::new(Point::new(0.0, 0.0), 3.0);
+
+        assert!(circle.tangent_lines_from(&Point::new(1.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_tangent_lines_from_none_on_boundary() {
+        let circle = Circle::new(Point::new(0.0, 0.0), 3.0);
+
+        assert!(circle.tangent_lines_from(&Point::new(3.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn test_tangent_lines_disjoint_circles_has_both_external_and_internal() {
+        let c1 = Circle::new(Point::new(0.0, 0.0), 2.0);
+        let c2 = Circle::new(Point::new(10.0, 0.0), 1.0);
+
+        let tangents = c1.tangent_lines(&c2);
+        // 2 external + 2 internal, since the circles are disjoint.
+        assert_eq!(tangents.len(), 4);
+        for (touch1, touch2) in &tangents {
+            assert!((c1.center.distance_to(touch1) - c1.radius).abs() < 1e-9);
+            assert!((c2.center.distance_to(touch2) - c2.radius).abs() < 1e-9);
         }
     }
+
+    #[test]
+    fn test_tangent_lines_overlapping_circles_external_only() {
+        let c1 = Circle::new(Point::new(0.0, 0.0), 2.0);
+        let c2 = Circle::new(Point::new(1.0, 0.0), 2.0);
+
+        // Overlapping circles have no internal tangent (d < r1 + r2), only external ones.
+        let tangents = c1.tangent_lines(&c2);
+        assert_eq!(tangents.len(), 2);
+    }
+
+    #[test]
+    fn test_tangent_lines_empty_when_one_circle_contains_the_other() {
+        let c1 = Circle::new(Point::new(0.0, 0.0), 1.0);
+        let c2 = Circle::new(Point::new(0.2, 0.0), 5.0);
+
+        assert!(c1.tangent_lines(&c2).is_empty());
+    }
+
+    #[test]
+    fn test_tangent_lines_single_tangency_when_internally_tangent() {
+        // d == |r1 - r2|: circles touch at exactly one point, so only one external tangent.
+        let c1 = Circle::new(Point::new(0.0, 0.0), 1.0);
+        let c2 = Circle::new(Point::new(2.0, 0.0), 3.0);
+
+        let tangents = c1.tangent_lines(&c2);
+        assert_eq!(tangents.len(), 1);
+    }
+
+    #[test]
+    fn test_line_intersections_two_points_through_center() {
+        let circle = Circle::new(Point::new(0.0, 0.0), 2.0);
+
+        let points = circle.line_intersections(&Point::new(-5.0, 0.0), &Point::new(5.0, 0.0));
+        assert_eq!(points.len(), 2);
+        for point in &points {
+            assert!((circle.center.distance_to(point) - circle.radius).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_line_intersections_vertical_line_uses_y_branch() {
+        // a.x == b.x makes cb == 0, exercising the near-vertical-line branch.
+        let circle = Circle::new(Point::new(0.0, 0.0), 2.0);
+
+        let points = circle.line_intersections(&Point::new(0.0, -5.0), &Point::new(0.0, 5.0));
+        assert_eq!(points.len(), 2);
+        for point in &points {
+            assert!((circle.center.distance_to(point) - circle.radius).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_line_intersections_tangent_line_single_point() {
+        let circle = Circle::new(Point::new(0.0, 0.0), 2.0);
+
+        let points = circle.line_intersections(&Point::new(-5.0, 2.0), &Point::new(5.0, 2.0));
+        assert_eq!(points.len(), 1);
+        assert!((points[0].x).abs() < 1e-6);
+        assert!((points[0].y - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_line_intersections_none_when_line_misses_circle() {
+        let circle = Circle::new(Point::new(0.0, 0.0), 2.0);
+
+        let points = circle.line_intersections(&Point::new(-5.0, 10.0), &Point::new(5.0, 10.0));
+        assert!(points.is_empty());
+    }
+
+    #[test]
+    fn test_segment_intersections_rejects_roots_outside_the_segment() {
+        let circle = Circle::new(Point::new(0.0, 0.0), 2.0);
+
+        // The line through these endpoints crosses the circle twice, but the segment itself
+        // only spans x in [3, 5] - well clear of the circle.
+        let points = circle.segment_intersections(&Point::new(3.0, 0.0), &Point::new(5.0, 0.0));
+        assert!(points.is_empty());
+    }
+
+    #[test]
+    fn test_segment_intersections_keeps_roots_on_the_segment() {
+        let circle = Circle::new(Point::new(0.0, 0.0), 2.0);
+
+        let points = circle.segment_intersections(&Point::new(-5.0, 0.0), &Point::new(5.0, 0.0));
+        assert_eq!(points.len(), 2);
+    }
+
+    #[test]
+    fn test_annulus_contains_point_between_inner_and_outer() {
+        let annulus = Annulus::new(Point::new(0.0, 0.0), 2.0, 5.0);
+
+        assert!(annulus.contains_point(&Point::new(3.0, 0.0)));
+        assert!(!annulus.contains_point(&Point::new(1.0, 0.0))); // Inside the inner standoff.
+        assert!(!annulus.contains_point(&Point::new(10.0, 0.0))); // Beyond the outer reach.
+    }
+
+    #[test]
+    fn test_annulus_fast_disjoint_when_box_entirely_outside_outer() {
+        let annulus = Annulus::new(Point::new(0.0, 0.0), 1.0, 2.0);
+        let far_box = AABB::new(Point::new(10.0, 10.0), Point::new(11.0, 11.0));
+
+        assert!(annulus.fast_disjoint(&far_box));
+    }
+
+    #[test]
+    fn test_annulus_fast_disjoint_when_box_entirely_inside_inner() {
+        let annulus = Annulus::new(Point::new(0.0, 0.0), 5.0, 10.0);
+        let tiny_box = AABB::new(Point::new(-0.5, -0.5), Point::new(0.5, 0.5));
+
+        assert!(annulus.fast_disjoint(&tiny_box));
+    }
+
+    #[test]
+    fn test_annulus_not_disjoint_when_box_straddles_the_ring() {
+        let annulus = Annulus::new(Point::new(0.0, 0.0), 2.0, 5.0);
+        let straddling_box = AABB::new(Point::new(-10.0, -1.0), Point::new(10.0, 1.0));
+
+        assert!(!annulus.fast_disjoint(&straddling_box));
+    }
+
+    #[test]
+    fn test_annulus_fast_contains_when_every_corner_in_range() {
+        let annulus = Annulus::new(Point::new(0.0, 0.0), 2.0, 10.0);
+        let box_in_ring = AABB::new(Point::new(3.0, 3.0), Point::new(4.0, 4.0));
+
+        assert!(annulus.fast_contains(&box_in_ring));
+    }
+
+    #[test]
+    fn test_annulus_fast_contains_false_when_a_corner_is_outside() {
+        let annulus = Annulus::new(Point::new(0.0, 0.0), 2.0, 5.0);
+        let box_spanning_boundary = AABB::new(Point::new(-1.0, -1.0), Point::new(4.0, 4.0));
+
+        assert!(!annulus.fast_contains(&box_spanning_boundary));
+    }
+
+    #[test]
+    fn test_region_circle_contains_point() {
+        let region = Region::Circle(Circle::new(Point::new(0.0, 0.0), 2.0));
+
+        assert!(region.contains_point(&Point::new(1.0, 0.0)));
+        assert!(!region.contains_point(&Point::new(3.0, 0.0)));
+    }
+
+    #[test]
+    fn test_region_half_plane_contains_point() {
+        // Everything with x <= 0.
+        let region = Region::HalfPlane { normal: Point::new(1.0, 0.0), offset: 0.0 };
+
+        assert!(region.contains_point(&Point::new(-1.0, 0.0)));
+        assert!(!region.contains_point(&Point::new(1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_region_annulus_via_difference() {
+        // An annular ring: inside the outer disk but outside the inner disk.
+        let outer = Region::Circle(Circle::new(Point::new(0.0, 0.0), 5.0));
+        let inner = Region::Circle(Circle::new(Point::new(0.0, 0.0), 2.0));
+        let ring = Region::Difference(Box::new(outer), Box::new(inner));
+
+        assert!(!ring.contains_point(&Point::new(0.0, 0.0))); // inside the hole
+        assert!(ring.contains_point(&Point::new(3.0, 0.0))); // inside the ring itself
+        assert!(!ring.contains_point(&Point::new(6.0, 0.0))); // outside the outer disk
+    }
+
+    #[test]
+    fn test_region_union_and_intersection() {
+        let left = Region::Circle(Circle::new(Point::new(-1.0, 0.0), 1.5));
+        let right = Region::Circle(Circle::new(Point::new(1.0, 0.0), 1.5));
+
+        let union = Region::Union(Box::new(left.clone()), Box::new(right.clone()));
+        assert!(union.contains_point(&Point::new(-1.0, 0.0)));
+        assert!(union.contains_point(&Point::new(1.0, 0.0)));
+        assert!(!union.contains_point(&Point::new(10.0, 0.0)));
+
+        let intersection = Region::Intersection(Box::new(left), Box::new(right));
+        assert!(intersection.contains_point(&Point::new(0.0, 0.0))); // where the two disks overlap
+        assert!(!intersection.contains_point(&Point::new(-1.0, 0.0))); // only in the left disk
+    }
+
+    #[test]
+    fn test_region_translate_shifts_membership() {
+        let circle = Region::Circle(Circle::new(Point::new(0.0, 0.0), 1.0));
+        let shifted = Region::Translate(Box::new(circle), Point::new(5.0, 0.0));
+
+        assert!(shifted.contains_point(&Point::new(5.0, 0.0)));
+        assert!(!shifted.contains_point(&Point::new(0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_arc_overlaps_handles_wraparound() {
+        // Arc wrapping from 5.5 rad through 0 to 1.0 rad, vs. a plain arc near 0.
+        let wrapping = Arc::new(5.5, 1.0);
+        let plain = Arc::new(0.5, 1.5);
+
+        // Overlap should be [0.5, 1.0] = 0.5, not 0 (which the old max/min logic would give).
+        assert!((wrapping.overlaps(&plain) - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_arc_union_merges_overlapping_arcs() {
+        let arcs = vec![Arc::new(0.0, 1.0), Arc::new(0.5, 2.0)];
+        let merged = Arc::union(&arcs);
+
+        assert_eq!(merged.len(), 1);
+        assert!((merged[0].length() - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_arc_union_stitches_the_seam() {
+        // One arc covering [5.5, 2*PI) and another covering [0, 1.0) are contiguous across
+        // the wrap and should merge into a single arc.
+        let arcs = vec![Arc::new(5.5, 2.0 * PI), Arc::new(0.0, 1.0)];
+        let merged = Arc::union(&arcs);
+
+        assert_eq!(merged.len(), 1);
+        assert!((merged[0].length() - (2.0 * PI - 5.5 + 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_arc_union_keeps_disjoint_arcs_separate() {
+        let arcs = vec![Arc::new(0.0, 0.5), Arc::new(3.0, 3.5)];
+        let merged = Arc::union(&arcs);
+
+        assert_eq!(merged.len(), 2);
+    }
+
+    #[test]
+    fn test_coverage_fraction_full_circle_is_one() {
+        let arcs = vec![Arc::new(0.0, PI), Arc::new(PI, 2.0 * PI)];
+        assert!((Arc::coverage_fraction(&arcs) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_coverage_fraction_partial_circle() {
+        let arcs = vec![Arc::new(0.0, PI)]; // half the circle
+        assert!((Arc::coverage_fraction(&arcs) - 0.5).abs() < 1e-9);
+    }
 }
\ No newline at end of file